@@ -0,0 +1,83 @@
+use {
+    super::digraph::Digraph,
+    crate::types::graph::{GlobalPosition, Graph, RelationKind},
+    serde::Serialize,
+    std::{collections::HashMap, hash::Hash},
+};
+
+#[derive(Debug, Default, Serialize)]
+pub struct Cycles {
+    /// Groups of mutually recursive symbols, including symbols calling themselves.
+    pub symbols: Vec<Vec<GlobalPosition>>,
+    /// Groups of file ids depending on each other.
+    pub files: Vec<Vec<u32>>,
+}
+
+/// Finds the strongly connected components of the call graph and of the file dependency graph,
+/// and flags the relations taking part in them.
+pub fn find_cycles(graph: &mut Graph) -> Cycles {
+    let symbols = strongly_connected_components(
+        graph
+            .relations
+            .iter()
+            .filter(|relation| matches!(relation.kind, RelationKind::Call))
+            .map(|relation| (relation.from, relation.to)),
+    );
+    let files = strongly_connected_components(
+        graph
+            .relations
+            .iter()
+            .map(|relation| (relation.from.file_id, relation.to.file_id))
+            .filter(|(from, to)| from != to),
+    );
+
+    let symbol_components = component_map(&symbols);
+    let file_components = component_map(&files);
+
+    for relation in graph.relations.iter_mut() {
+        relation.in_call_cycle = matches!(relation.kind, RelationKind::Call)
+            && symbol_components
+                .get(&relation.from)
+                .is_some_and(|c| symbol_components.get(&relation.to) == Some(c));
+        relation.in_file_cycle = relation.from.file_id != relation.to.file_id
+            && file_components
+                .get(&relation.from.file_id)
+                .is_some_and(|c| file_components.get(&relation.to.file_id) == Some(c));
+    }
+
+    Cycles { symbols, files }
+}
+
+fn component_map<N: Copy + Hash + Eq>(components: &[Vec<N>]) -> HashMap<N, usize> {
+    components
+        .iter()
+        .enumerate()
+        .flat_map(|(i, component)| component.iter().map(move |node| (*node, i)))
+        .collect()
+}
+
+/// Only components with more than one node or with a self loop are returned.
+fn strongly_connected_components<N: Copy + Hash + Ord>(
+    edges: impl Iterator<Item = (N, N)>,
+) -> Vec<Vec<N>> {
+    let graph = Digraph::from_edges(edges);
+
+    let mut components = graph
+        .strongly_connected_components()
+        .into_iter()
+        .filter(|component| {
+            component.len() > 1 || graph.successors[component[0]].contains(&component[0])
+        })
+        .map(|component| {
+            let mut component = component
+                .into_iter()
+                .map(|i| graph.nodes[i])
+                .collect::<Vec<_>>();
+            component.sort();
+            component
+        })
+        .collect::<Vec<_>>();
+
+    components.sort();
+    components
+}
@@ -0,0 +1,81 @@
+use {
+    crate::types::{
+        graph::{GlobalPosition, Graph, RelationKind, Symbol},
+        lsp::SymbolKind,
+    },
+    serde::Serialize,
+    std::collections::HashSet,
+};
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UnusedSymbol {
+    /// The workspace path of the file.
+    pub path: String,
+    pub position: GlobalPosition,
+    pub name: String,
+    pub qualified_name: String,
+    pub kind: SymbolKind,
+}
+
+/// Lists the functions and methods that are neither called by any other symbol nor entry points.
+pub fn find_dead_code(graph: &Graph, entry_points: &HashSet<GlobalPosition>) -> Vec<UnusedSymbol> {
+    let called = graph
+        .relations
+        .iter()
+        .filter(|relation| matches!(relation.kind, RelationKind::Call))
+        .filter(|relation| relation.from != relation.to)
+        .map(|relation| relation.to)
+        .collect::<HashSet<_>>();
+
+    let mut unused = vec![];
+    for file in &graph.files {
+        collect_unused(
+            &file.workspace_path(),
+            file.id,
+            &file.symbols,
+            &called,
+            entry_points,
+            &mut unused,
+        );
+    }
+
+    unused
+}
+
+fn collect_unused(
+    path: &str,
+    file_id: u32,
+    symbols: &[Symbol],
+    called: &HashSet<GlobalPosition>,
+    entry_points: &HashSet<GlobalPosition>,
+    unused: &mut Vec<UnusedSymbol>,
+) {
+    for symbol in symbols {
+        let position = GlobalPosition::new(file_id, symbol.selection_range.start);
+
+        if matches!(
+            symbol.kind,
+            SymbolKind::Function | SymbolKind::Method | SymbolKind::Constructor
+        ) && !called.contains(&position)
+            && !entry_points.contains(&position)
+        {
+            unused.push(UnusedSymbol {
+                path: path.to_string(),
+                position,
+                name: symbol.name.clone(),
+                qualified_name: symbol.qualified_name.clone(),
+                kind: symbol.kind,
+            });
+        }
+
+        collect_unused(
+            path,
+            file_id,
+            &symbol.children,
+            called,
+            entry_points,
+            unused,
+        );
+    }
+}
@@ -0,0 +1,77 @@
+use {
+    crate::types::graph::{File, Graph, RelationKind, Symbol},
+    serde::Serialize,
+    std::collections::BTreeSet,
+};
+
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RelationIdentity {
+    pub from_id: String,
+    pub to_id: String,
+    pub kind: RelationKind,
+}
+
+#[derive(Debug, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GraphDiff {
+    /// [Workspace paths](File::workspace_path) of the added files.
+    pub added_files: Vec<String>,
+    /// [Workspace paths](File::workspace_path) of the removed files.
+    pub removed_files: Vec<String>,
+    /// Ids of the added symbols.
+    pub added_symbols: Vec<String>,
+    /// Ids of the removed symbols.
+    pub removed_symbols: Vec<String>,
+    pub added_relations: Vec<RelationIdentity>,
+    pub removed_relations: Vec<RelationIdentity>,
+}
+
+/// Compares two graphs, e.g. generated from two branches, matching files by their path
+/// under their workspace root, so that two checkouts of a repository can be compared,
+/// and symbols by their ids instead of their positions.
+pub fn diff_graphs(old: &Graph, new: &Graph) -> GraphDiff {
+    let (old_files, new_files) = (file_paths(old), file_paths(new));
+    let (old_symbols, new_symbols) = (symbol_ids(old), symbol_ids(new));
+    let (old_relations, new_relations) = (relation_identities(old), relation_identities(new));
+
+    GraphDiff {
+        added_files: new_files.difference(&old_files).cloned().collect(),
+        removed_files: old_files.difference(&new_files).cloned().collect(),
+        added_symbols: new_symbols.difference(&old_symbols).cloned().collect(),
+        removed_symbols: old_symbols.difference(&new_symbols).cloned().collect(),
+        added_relations: new_relations.difference(&old_relations).cloned().collect(),
+        removed_relations: old_relations.difference(&new_relations).cloned().collect(),
+    }
+}
+
+fn file_paths(graph: &Graph) -> BTreeSet<String> {
+    graph.files.iter().map(File::workspace_path).collect()
+}
+
+fn symbol_ids(graph: &Graph) -> BTreeSet<String> {
+    let mut ids = BTreeSet::new();
+    for file in &graph.files {
+        collect_ids(&file.symbols, &mut ids);
+    }
+    ids
+}
+
+fn collect_ids(symbols: &[Symbol], ids: &mut BTreeSet<String>) {
+    for symbol in symbols {
+        ids.insert(symbol.id.clone());
+        collect_ids(&symbol.children, ids);
+    }
+}
+
+fn relation_identities(graph: &Graph) -> BTreeSet<RelationIdentity> {
+    graph
+        .relations
+        .iter()
+        .map(|relation| RelationIdentity {
+            from_id: relation.from_id.clone(),
+            to_id: relation.to_id.clone(),
+            kind: relation.kind,
+        })
+        .collect()
+}
@@ -0,0 +1,126 @@
+use std::{
+    collections::{HashMap, HashSet},
+    hash::Hash,
+};
+
+/// A directed graph without parallel edges, with nodes indexed in insertion order.
+pub(super) struct Digraph<N> {
+    pub nodes: Vec<N>,
+    pub successors: Vec<Vec<usize>>,
+    pub predecessors: Vec<Vec<usize>>,
+
+    ids: HashMap<N, usize>,
+    edges: HashSet<(usize, usize)>,
+}
+
+impl<N: Copy + Hash + Eq> Digraph<N> {
+    pub fn new() -> Self {
+        Self {
+            nodes: vec![],
+            successors: vec![],
+            predecessors: vec![],
+
+            ids: HashMap::new(),
+            edges: HashSet::new(),
+        }
+    }
+
+    pub fn from_edges(edges: impl Iterator<Item = (N, N)>) -> Self {
+        let mut graph = Self::new();
+        for (from, to) in edges {
+            graph.add_edge(from, to);
+        }
+        graph
+    }
+
+    pub fn add_node(&mut self, node: N) -> usize {
+        *self.ids.entry(node).or_insert_with(|| {
+            self.nodes.push(node);
+            self.successors.push(vec![]);
+            self.predecessors.push(vec![]);
+            self.nodes.len() - 1
+        })
+    }
+
+    pub fn add_edge(&mut self, from: N, to: N) {
+        let from = self.add_node(from);
+        let to = self.add_node(to);
+
+        if self.edges.insert((from, to)) {
+            self.successors[from].push(to);
+            self.predecessors[to].push(from);
+        }
+    }
+}
+
+impl<N> Digraph<N> {
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    /// Tarjan's algorithm, iterative so that long call chains don't overflow the stack.
+    /// Returns the indices of the nodes of every component, components reachable from
+    /// another one coming before it.
+    pub fn strongly_connected_components(&self) -> Vec<Vec<usize>> {
+        const UNVISITED: usize = usize::MAX;
+
+        let adjacency = &self.successors;
+        let mut index = vec![UNVISITED; self.len()];
+        let mut lowlink = vec![0; self.len()];
+        let mut on_stack = vec![false; self.len()];
+        let mut stack = vec![];
+        let mut next_index = 0;
+        let mut components = vec![];
+
+        for root in 0..self.len() {
+            if index[root] != UNVISITED {
+                continue;
+            }
+
+            let mut call_stack = vec![(root, 0)];
+            index[root] = next_index;
+            lowlink[root] = next_index;
+            next_index += 1;
+            stack.push(root);
+            on_stack[root] = true;
+
+            while let Some(&(v, i)) = call_stack.last() {
+                if let Some(&w) = adjacency[v].get(i) {
+                    call_stack.last_mut().unwrap().1 += 1;
+
+                    if index[w] == UNVISITED {
+                        index[w] = next_index;
+                        lowlink[w] = next_index;
+                        next_index += 1;
+                        stack.push(w);
+                        on_stack[w] = true;
+                        call_stack.push((w, 0));
+                    } else if on_stack[w] {
+                        lowlink[v] = lowlink[v].min(index[w]);
+                    }
+                    continue;
+                }
+
+                call_stack.pop();
+                if let Some(&(u, _)) = call_stack.last() {
+                    lowlink[u] = lowlink[u].min(lowlink[v]);
+                }
+
+                if lowlink[v] == index[v] {
+                    let mut component = vec![];
+                    loop {
+                        let w = stack.pop().unwrap();
+                        on_stack[w] = false;
+                        component.push(w);
+                        if w == v {
+                            break;
+                        }
+                    }
+                    components.push(component);
+                }
+            }
+        }
+
+        components
+    }
+}
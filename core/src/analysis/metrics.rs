@@ -0,0 +1,212 @@
+use {
+    super::digraph::Digraph,
+    crate::types::graph::{GlobalPosition, Graph, RelationKind, Symbol},
+    serde::Serialize,
+    std::{collections::VecDeque, hash::Hash},
+};
+
+const DAMPING_FACTOR: f64 = 0.85;
+const MAX_ITERATIONS: usize = 100;
+const TOLERANCE: f64 = 1e-9;
+
+#[derive(Debug, Default, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Metrics {
+    /// Number of distinct callers, or files depending on the file.
+    pub fan_in: u32,
+    /// Number of distinct callees, or files the file depends on.
+    pub fan_out: u32,
+    /// Number of symbols, or files, reaching this one through any chain of relations.
+    /// Only computed on request, as it takes quadratic time on large graphs.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub transitive_callers: Option<u32>,
+    /// PageRank score, the scores of all symbols, or all files, add up to 1.
+    pub page_rank: f64,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SymbolMetrics {
+    pub position: GlobalPosition,
+    #[serde(flatten)]
+    pub metrics: Metrics,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FileMetrics {
+    pub file_id: u32,
+    #[serde(flatten)]
+    pub metrics: Metrics,
+}
+
+#[derive(Debug, Serialize)]
+pub struct GraphMetrics {
+    pub symbols: Vec<SymbolMetrics>,
+    pub files: Vec<FileMetrics>,
+}
+
+/// Computes the metrics of every symbol over the calls between them,
+/// and of every file over the relations crossing files.
+/// The number of transitive callers is left out unless `transitive_callers` is set.
+pub fn compute_metrics(graph: &Graph, transitive_callers: bool) -> GraphMetrics {
+    let mut symbol_graph = Digraph::new();
+    for file in &graph.files {
+        add_symbols(&mut symbol_graph, file.id, &file.symbols);
+    }
+    for relation in graph
+        .relations
+        .iter()
+        .filter(|relation| matches!(relation.kind, RelationKind::Call))
+    {
+        symbol_graph.add_edge(relation.from, relation.to);
+    }
+
+    let mut file_graph = Digraph::new();
+    for file in &graph.files {
+        file_graph.add_node(file.id);
+    }
+    for relation in graph
+        .relations
+        .iter()
+        .filter(|relation| relation.from.file_id != relation.to.file_id)
+    {
+        file_graph.add_edge(relation.from.file_id, relation.to.file_id);
+    }
+
+    GraphMetrics {
+        symbols: node_metrics(&symbol_graph, transitive_callers)
+            .map(|(position, metrics)| SymbolMetrics { position, metrics })
+            .collect(),
+        files: node_metrics(&file_graph, transitive_callers)
+            .map(|(file_id, metrics)| FileMetrics { file_id, metrics })
+            .collect(),
+    }
+}
+
+fn add_symbols(graph: &mut Digraph<GlobalPosition>, file_id: u32, symbols: &[Symbol]) {
+    for symbol in symbols {
+        graph.add_node(GlobalPosition::new(file_id, symbol.selection_range.start));
+        add_symbols(graph, file_id, &symbol.children);
+    }
+}
+
+fn node_metrics<N: Copy + Hash + Eq>(
+    graph: &Digraph<N>,
+    transitive_callers: bool,
+) -> impl Iterator<Item = (N, Metrics)> + '_ {
+    let page_rank = page_rank(graph);
+    let transitive_callers = transitive_callers.then(|| transitive_predecessors(graph));
+
+    (0..graph.len()).map(move |i| {
+        let fan_in = graph.predecessors[i].iter().filter(|&&j| j != i).count();
+        let fan_out = graph.successors[i].iter().filter(|&&j| j != i).count();
+
+        (
+            graph.nodes[i],
+            Metrics {
+                fan_in: fan_in as u32,
+                fan_out: fan_out as u32,
+                transitive_callers: transitive_callers.as_ref().map(|counts| counts[i] as u32),
+                page_rank: page_rank[i],
+            },
+        )
+    })
+}
+
+/// Counts the nodes reaching each node, walking the graph of its strongly connected components
+/// so that the nodes of a cycle are walked once rather than once per node of the cycle.
+fn transitive_predecessors<N>(graph: &Digraph<N>) -> Vec<usize> {
+    let components = graph.strongly_connected_components();
+    let mut component_of = vec![0; graph.len()];
+    for (c, component) in components.iter().enumerate() {
+        for &i in component {
+            component_of[i] = c;
+        }
+    }
+
+    let mut predecessors = vec![vec![]; components.len()];
+    for (i, node_predecessors) in graph.predecessors.iter().enumerate() {
+        for &j in node_predecessors {
+            let (c, d) = (component_of[i], component_of[j]);
+            if c != d {
+                predecessors[c].push(d);
+            }
+        }
+    }
+    for component_predecessors in &mut predecessors {
+        component_predecessors.sort_unstable();
+        component_predecessors.dedup();
+    }
+
+    // the component each one was last reached from, so that the marks are not reset between walks
+    let mut reached_from = vec![usize::MAX; components.len()];
+    let counts = (0..components.len())
+        .map(|c| {
+            reached_from[c] = c;
+            let mut queue = VecDeque::from([c]);
+            let mut count = 0;
+
+            while let Some(d) = queue.pop_front() {
+                for &e in &predecessors[d] {
+                    if reached_from[e] != c {
+                        reached_from[e] = c;
+                        count += components[e].len();
+                        queue.push_back(e);
+                    }
+                }
+            }
+
+            count
+        })
+        .collect::<Vec<_>>();
+
+    // the other nodes of a component reach each node of it
+    (0..graph.len())
+        .map(|i| {
+            let c = component_of[i];
+            counts[c] + components[c].len() - 1
+        })
+        .collect()
+}
+
+/// The rank of nodes without outgoing edges is spread evenly over all nodes.
+fn page_rank<N>(graph: &Digraph<N>) -> Vec<f64> {
+    let n = graph.len();
+    if n == 0 {
+        return vec![];
+    }
+
+    let mut ranks = vec![1.0 / n as f64; n];
+
+    for _ in 0..MAX_ITERATIONS {
+        let dangling = (0..n)
+            .filter(|&i| graph.successors[i].is_empty())
+            .map(|i| ranks[i])
+            .sum::<f64>();
+        let base = (1.0 - DAMPING_FACTOR + DAMPING_FACTOR * dangling) / n as f64;
+
+        let next = (0..n)
+            .map(|i| {
+                base + DAMPING_FACTOR
+                    * graph.predecessors[i]
+                        .iter()
+                        .map(|&j| ranks[j] / graph.successors[j].len() as f64)
+                        .sum::<f64>()
+            })
+            .collect::<Vec<_>>();
+
+        let delta = next
+            .iter()
+            .zip(&ranks)
+            .map(|(a, b)| (a - b).abs())
+            .sum::<f64>();
+        ranks = next;
+
+        if delta < TOLERANCE {
+            break;
+        }
+    }
+
+    ranks
+}
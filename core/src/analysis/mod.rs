@@ -0,0 +1,16 @@
+//! Analysis passes over generated graphs.
+
+mod cycles;
+mod dead_code;
+mod diff;
+mod digraph;
+mod metrics;
+#[cfg(test)]
+mod tests;
+
+pub use {
+    cycles::{find_cycles, Cycles},
+    dead_code::{find_dead_code, UnusedSymbol},
+    diff::{diff_graphs, GraphDiff, RelationIdentity},
+    metrics::{compute_metrics, FileMetrics, GraphMetrics, Metrics, SymbolMetrics},
+};
@@ -0,0 +1,216 @@
+use {
+    super::{compute_metrics, diff_graphs, find_cycles},
+    crate::types::{
+        graph::{File, GlobalPosition, Graph, Relation, RelationKind, Symbol},
+        lsp::{Position, Range, SymbolKind},
+    },
+};
+
+fn pos(file_id: u32, line: u32) -> GlobalPosition {
+    GlobalPosition::new(file_id, Position { line, character: 0 })
+}
+
+fn func(name: &str, line: u32) -> Symbol {
+    let start = Position { line, character: 0 };
+    Symbol {
+        id: name.to_string(),
+        name: name.to_string(),
+        qualified_name: name.to_string(),
+        detail: None,
+        kind: SymbolKind::Function,
+        tags: vec![],
+        range: Range { start, end: start },
+        selection_range: Range { start, end: start },
+        children: vec![],
+    }
+}
+
+fn file(id: u32, lines: &[u32]) -> File {
+    let symbols = lines
+        .iter()
+        .map(|&line| func(&format!("f{line}"), line))
+        .collect();
+    file_at(id, &format!("{id}.rs"), symbols)
+}
+
+fn file_at(id: u32, path: &str, symbols: Vec<Symbol>) -> File {
+    File {
+        id,
+        path: path.to_string(),
+        root: None,
+        relative_path: path.to_string(),
+        symbols,
+    }
+}
+
+fn call(from: GlobalPosition, to: GlobalPosition) -> Relation {
+    Relation::new(from, to, RelationKind::Call, vec![])
+}
+
+fn call_by_id(from: &str, to: &str) -> Relation {
+    Relation {
+        from_id: from.to_string(),
+        to_id: to.to_string(),
+        ..call(pos(0, 0), pos(0, 0))
+    }
+}
+
+#[test]
+fn cycles() {
+    let mut graph = Graph {
+        roots: vec![],
+        files: vec![],
+        externals: vec![],
+        relations: vec![
+            // mutual recursion inside file 1
+            call(pos(1, 1), pos(1, 2)),
+            call(pos(1, 2), pos(1, 1)),
+            // recursion
+            call(pos(1, 3), pos(1, 3)),
+            // files 2 and 3 depend on each other without any recursion
+            call(pos(2, 1), pos(3, 1)),
+            call(pos(3, 2), pos(2, 2)),
+            call(pos(1, 1), pos(2, 1)),
+        ],
+    };
+
+    let cycles = find_cycles(&mut graph);
+
+    assert_eq!(
+        cycles.symbols,
+        [vec![pos(1, 1), pos(1, 2)], vec![pos(1, 3)]]
+    );
+    assert_eq!(cycles.files, [vec![2, 3]]);
+
+    let flags = graph
+        .relations
+        .iter()
+        .map(|r| (r.in_call_cycle, r.in_file_cycle))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        flags,
+        [
+            (true, false),
+            (true, false),
+            (true, false),
+            (false, true),
+            (false, true),
+            (false, false),
+        ]
+    );
+}
+
+#[test]
+fn metrics() {
+    // 1:1 -> 1:2 -> 2:1, 1:3 -> 2:1, 1:4 isolated
+    let graph = Graph {
+        roots: vec![],
+        files: vec![file(1, &[1, 2, 3, 4]), file(2, &[1])],
+        externals: vec![],
+        relations: vec![
+            call(pos(1, 1), pos(1, 2)),
+            call(pos(1, 2), pos(2, 1)),
+            call(pos(1, 3), pos(2, 1)),
+        ],
+    };
+
+    assert!(compute_metrics(&graph, false)
+        .symbols
+        .iter()
+        .all(|s| s.metrics.transitive_callers.is_none()));
+
+    let metrics = compute_metrics(&graph, true);
+
+    let sink = metrics
+        .symbols
+        .iter()
+        .find(|s| s.position == pos(2, 1))
+        .unwrap();
+    assert_eq!(
+        (
+            sink.metrics.fan_in,
+            sink.metrics.fan_out,
+            sink.metrics.transitive_callers
+        ),
+        (2, 0, Some(3))
+    );
+
+    let total = metrics
+        .symbols
+        .iter()
+        .map(|s| s.metrics.page_rank)
+        .sum::<f64>();
+    assert!((total - 1.0).abs() < 1e-6);
+    assert!(metrics
+        .symbols
+        .iter()
+        .all(|s| s.metrics.page_rank <= sink.metrics.page_rank));
+
+    assert_eq!(metrics.symbols.len(), 5);
+    assert_eq!(metrics.files.len(), 2);
+    assert_eq!(metrics.files[0].metrics.fan_out, 1);
+    assert_eq!(metrics.files[1].metrics.fan_in, 1);
+}
+
+#[test]
+fn transitive_callers_through_cycles() {
+    // 1:4 -> 1:1 <-> 1:2 -> 1:3
+    let graph = Graph {
+        roots: vec![],
+        files: vec![file(1, &[1, 2, 3, 4])],
+        externals: vec![],
+        relations: vec![
+            call(pos(1, 1), pos(1, 2)),
+            call(pos(1, 2), pos(1, 1)),
+            call(pos(1, 2), pos(1, 3)),
+            call(pos(1, 4), pos(1, 1)),
+        ],
+    };
+
+    let metrics = compute_metrics(&graph, true);
+    let callers = (1..=4)
+        .map(|line| {
+            let symbol = metrics.symbols.iter().find(|s| s.position == pos(1, line));
+            symbol.unwrap().metrics.transitive_callers.unwrap()
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(callers, [2, 2, 3, 0]);
+}
+
+#[test]
+fn diff() {
+    let old = Graph {
+        roots: vec![],
+        files: vec![
+            file_at(1, "a.rs", vec![func("main", 1), func("parse", 5)]),
+            file_at(2, "b.rs", vec![]),
+        ],
+        externals: vec![],
+        relations: vec![call_by_id("main", "parse")],
+    };
+    // lines shifted, `parse` no longer called, `run` added
+    let new = Graph {
+        roots: vec![],
+        files: vec![
+            file_at(1, "0.rs", vec![]),
+            file_at(
+                2,
+                "a.rs",
+                vec![func("main", 3), func("parse", 8), func("run", 12)],
+            ),
+        ],
+        externals: vec![],
+        relations: vec![call_by_id("main", "run")],
+    };
+
+    let diff = diff_graphs(&old, &new);
+
+    assert_eq!(diff.added_files, ["0.rs"]);
+    assert_eq!(diff.removed_files, ["b.rs"]);
+    assert!(diff.removed_symbols.is_empty());
+    assert_eq!(diff.added_symbols, ["run"]);
+    assert_eq!(diff.added_relations.len(), 1);
+    assert_eq!(diff.added_relations[0].to_id, "run");
+    assert_eq!(diff.removed_relations.len(), 1);
+    assert_eq!(diff.removed_relations[0].to_id, "parse");
+}
@@ -0,0 +1,37 @@
+use std::fmt;
+
+#[derive(Debug)]
+pub enum Error {
+    /// An argument passed by the editor could not be deserialized.
+    InvalidArgument { name: &'static str, reason: String },
+    /// A result could not be serialized for the editor.
+    Serialization(String),
+    /// The saved state is malformed.
+    InvalidState(serde_json::Error),
+    /// The saved state was written by an incompatible version.
+    UnsupportedStateVersion(u32),
+}
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::InvalidArgument { name, reason } => write!(f, "invalid `{name}`: {reason}"),
+            Error::Serialization(reason) => write!(f, "failed to serialize the result: {reason}"),
+            Error::InvalidState(err) => write!(f, "malformed state: {err}"),
+            Error::UnsupportedStateVersion(version) => {
+                write!(f, "unsupported state version {version}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::InvalidState(err) => Some(err),
+            _ => None,
+        }
+    }
+}
@@ -0,0 +1,134 @@
+use {
+    super::GraphGenerator,
+    crate::types::graph::{
+        Dependency, DependencyGraph, DependencyNode, Graph, RelationCounts, Symbol,
+    },
+    std::collections::{BTreeMap, HashMap, HashSet},
+};
+
+impl GraphGenerator {
+    /// Generates a graph of files, weighting each dependency by the symbol relations it summarizes.
+    pub fn gen_file_graph(&self) -> DependencyGraph {
+        aggregate_files(&self.gen_graph())
+    }
+
+    /// Generates a graph of directories, or of packages and modules depending on the language,
+    /// keeping `depth` levels below the common ancestor of all files.
+    pub fn gen_directory_graph(&self, depth: usize) -> DependencyGraph {
+        let file_graph = aggregate_files(&self.gen_graph());
+
+        let module_paths = file_graph
+            .nodes
+            .iter()
+            .map(|node| self.lang.module_path(&node.path))
+            .collect::<Vec<_>>();
+
+        // keep at least one level, so that there is something to name the groups after
+        let prefix_len = module_paths
+            .iter()
+            .map(|path| path.len().saturating_sub(1))
+            .min()
+            .unwrap_or(0);
+        let prefix_len = (0..prefix_len)
+            .take_while(|&i| {
+                module_paths
+                    .iter()
+                    .all(|path| path[i] == module_paths[0][i])
+            })
+            .count();
+
+        let mut groups = BTreeMap::<String, DependencyNode>::new();
+        let mut group_of_file = HashMap::new();
+
+        for (node, path) in file_graph.nodes.iter().zip(&module_paths) {
+            let end = path.len().min(prefix_len + depth.max(1));
+            let key = path[prefix_len..end].join("/");
+
+            let group = groups.entry(key.clone()).or_insert_with(|| DependencyNode {
+                id: 0,
+                path: key.clone(),
+                symbol_count: 0,
+                file_count: 0,
+            });
+            group.symbol_count += node.symbol_count;
+            group.file_count += node.file_count;
+
+            group_of_file.insert(node.id, key);
+        }
+
+        for (i, group) in groups.values_mut().enumerate() {
+            group.id = i as u32 + 1;
+        }
+
+        let mut edges = BTreeMap::<(u32, u32), RelationCounts>::new();
+        for edge in &file_graph.edges {
+            let from = groups[&group_of_file[&edge.from]].id;
+            let to = groups[&group_of_file[&edge.to]].id;
+
+            if from != to {
+                edges.entry((from, to)).or_default().merge(&edge.counts);
+            }
+        }
+
+        DependencyGraph {
+            nodes: groups.into_values().collect(),
+            edges: collect_edges(edges),
+        }
+    }
+}
+
+fn aggregate_files(graph: &Graph) -> DependencyGraph {
+    let nodes = graph
+        .files
+        .iter()
+        .map(|file| DependencyNode {
+            id: file.id,
+            path: file.workspace_path(),
+            symbol_count: count_symbols(&file.symbols),
+            file_count: 1,
+        })
+        .collect();
+
+    // external files are left out of dependency graphs
+    let file_ids = graph
+        .files
+        .iter()
+        .map(|file| file.id)
+        .collect::<HashSet<_>>();
+
+    let mut edges = BTreeMap::<(u32, u32), RelationCounts>::new();
+    for relation in graph.relations.iter().filter(|relation| {
+        relation.from.file_id != relation.to.file_id
+            && file_ids.contains(&relation.from.file_id)
+            && file_ids.contains(&relation.to.file_id)
+    }) {
+        edges
+            .entry((relation.from.file_id, relation.to.file_id))
+            .or_default()
+            .add(&relation.kind);
+    }
+
+    DependencyGraph {
+        nodes,
+        edges: collect_edges(edges),
+    }
+}
+
+fn collect_edges(edges: BTreeMap<(u32, u32), RelationCounts>) -> Vec<Dependency> {
+    edges
+        .into_iter()
+        .map(|((from, to), counts)| Dependency {
+            from,
+            to,
+            weight: counts.total(),
+            counts,
+        })
+        .collect()
+}
+
+fn count_symbols(symbols: &[Symbol]) -> u32 {
+    symbols
+        .iter()
+        .map(|symbol| 1 + count_symbols(&symbol.children))
+        .sum()
+}
@@ -0,0 +1,177 @@
+use {
+    super::{path_position, GraphGenerator},
+    crate::{
+        error::{Error, Result},
+        types::{
+            graph::{GlobalPosition, PathPosition},
+            lsp::{
+                CallHierarchyIncomingCall, CallHierarchyOutgoingCall, DocumentSymbol, Position, Uri,
+            },
+        },
+    },
+    serde::{Deserialize, Serialize},
+    std::collections::HashMap,
+};
+
+const STATE_VERSION: u32 = 1;
+
+/// Data collected from the language server, keyed by paths instead of file ids,
+/// as file ids are only meaningful within one generator.
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct State {
+    version: u32,
+    files: Vec<FileState>,
+    incoming_calls: Vec<Keyed<Vec<CallHierarchyIncomingCall>>>,
+    outgoing_calls: Vec<Keyed<Vec<CallHierarchyOutgoingCall>>>,
+    interfaces: Vec<Keyed<Vec<PathPosition>>>,
+    supertypes: Vec<Keyed<Vec<PathPosition>>>,
+    subtypes: Vec<Keyed<Vec<PathPosition>>>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct FileState {
+    path: String,
+    hash: Option<String>,
+    symbols: Vec<DocumentSymbol>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct Keyed<T> {
+    path: String,
+    position: Position,
+    value: T,
+}
+
+impl GraphGenerator {
+    /// Records the hash of the file content the symbols and calls of the file are requested for.
+    pub fn record_file_content(&mut self, path: String, content: &str) {
+        let path = self.intern_path(&Uri::parse(&path));
+        self.file_hashes.insert(path, content_hash(content));
+    }
+
+    /// Whether the file is cached and its content has not changed since it was recorded.
+    pub fn is_file_unchanged(&self, path: &str, content: &str) -> bool {
+        let path = self.resolve_path(&Uri::parse(path));
+
+        self.files.contains_key(&path)
+            && self
+                .file_hashes
+                .get(&path)
+                .is_some_and(|hash| *hash == content_hash(content))
+    }
+
+    /// Serializes the collected data, so that a later session only has to request the changed files again.
+    pub fn save_state(&self) -> String {
+        let paths = self.file_paths();
+
+        let mut files = self
+            .files
+            .iter()
+            .map(|(path, symbols)| FileState {
+                path: path.clone(),
+                hash: self.file_hashes.get(path).cloned(),
+                symbols: symbols.clone(),
+            })
+            .collect::<Vec<_>>();
+        files.sort_by(|a, b| a.path.cmp(&b.path));
+
+        let positions = |positions: &Vec<GlobalPosition>| {
+            positions.iter().map(|p| path_position(&paths, p)).collect()
+        };
+
+        let state = State {
+            version: STATE_VERSION,
+            files,
+            incoming_calls: save_entries(&self.incoming_calls, &paths, Vec::clone),
+            outgoing_calls: save_entries(&self.outgoing_calls, &paths, Vec::clone),
+            interfaces: save_entries(&self.interfaces, &paths, positions),
+            supertypes: save_entries(&self.supertypes, &paths, positions),
+            subtypes: save_entries(&self.subtypes, &paths, positions),
+        };
+
+        serde_json::to_string(&state).unwrap()
+    }
+
+    /// Replaces the collected data with the data saved by [`save_state`](Self::save_state).
+    pub fn load_state(&mut self, state: &str) -> Result<()> {
+        let state = serde_json::from_str::<State>(state).map_err(Error::InvalidState)?;
+        if state.version != STATE_VERSION {
+            return Err(Error::UnsupportedStateVersion(state.version));
+        }
+
+        self.paths.clear();
+        self.file_id_map.clear();
+        self.files.clear();
+        self.rejected_files.clear();
+        self.file_hashes.clear();
+
+        for file in state.files {
+            let path = self.intern_path(&Uri::parse(&file.path));
+            if let Some(hash) = file.hash {
+                self.file_hashes.insert(path.clone(), hash);
+            }
+            self.alloc_file_id(path.clone());
+            self.files.insert(path, file.symbols);
+        }
+
+        self.incoming_calls = self.load_entries(state.incoming_calls, |_, calls| calls);
+        self.outgoing_calls = self.load_entries(state.outgoing_calls, |_, calls| calls);
+        self.interfaces = self.load_entries(state.interfaces, Self::load_positions);
+        self.supertypes = self.load_entries(state.supertypes, Self::load_positions);
+        self.subtypes = self.load_entries(state.subtypes, Self::load_positions);
+
+        Ok(())
+    }
+
+    fn load_entries<T, U>(
+        &mut self,
+        entries: Vec<Keyed<T>>,
+        convert: impl Fn(&mut Self, T) -> U,
+    ) -> HashMap<GlobalPosition, U> {
+        entries
+            .into_iter()
+            .map(|entry| {
+                let key = self.global_position(&entry.path, entry.position);
+                (key, convert(self, entry.value))
+            })
+            .collect()
+    }
+
+    fn load_positions(&mut self, positions: Vec<PathPosition>) -> Vec<GlobalPosition> {
+        positions
+            .into_iter()
+            .map(|p| self.global_position(&p.path, p.position))
+            .collect()
+    }
+}
+
+fn save_entries<T, U>(
+    map: &HashMap<GlobalPosition, T>,
+    paths: &HashMap<u32, &String>,
+    convert: impl Fn(&T) -> U,
+) -> Vec<Keyed<U>> {
+    let mut entries = map
+        .iter()
+        .map(|(key, value)| {
+            let key = path_position(paths, key);
+            Keyed {
+                path: key.path,
+                position: key.position,
+                value: convert(value),
+            }
+        })
+        .collect::<Vec<_>>();
+    entries.sort_by(|a, b| (&a.path, a.position).cmp(&(&b.path, b.position)));
+
+    entries
+}
+
+/// 64-bit FNV-1a, which unlike the hasher of the standard library is stable across releases.
+fn content_hash(content: &str) -> String {
+    let hash = content.bytes().fold(0xcbf29ce484222325_u64, |hash, byte| {
+        (hash ^ byte as u64).wrapping_mul(0x100000001b3)
+    });
+
+    format!("{hash:016x}")
+}
@@ -0,0 +1,124 @@
+use {
+    super::path_position,
+    crate::types::{
+        graph::{
+            Diagnostics, DropReason, DroppedRelation, GlobalPosition, PathPosition, Relation,
+            RelationKind, UnresolvedCall,
+        },
+        lsp::CallHierarchyItem,
+    },
+    std::{cell::RefCell, collections::HashMap},
+};
+
+/// Collects the reasons relations are left out while generating a graph.
+pub(super) struct Collector<'a> {
+    paths: HashMap<u32, &'a String>,
+    diagnostics: RefCell<Diagnostics>,
+}
+
+impl<'a> Collector<'a> {
+    pub(super) fn new(paths: HashMap<u32, &'a String>) -> Self {
+        Self {
+            paths,
+            diagnostics: RefCell::new(Diagnostics::default()),
+        }
+    }
+
+    /// Keeps the relation if both ends are symbols, recording why it is dropped otherwise.
+    pub(super) fn check(
+        &self,
+        symbols: &HashMap<GlobalPosition, String>,
+        relation: Relation,
+    ) -> Option<Relation> {
+        let reason = if !symbols.contains_key(&relation.from) {
+            DropReason::UnknownSource
+        } else if !symbols.contains_key(&relation.to) {
+            DropReason::UnknownTarget
+        } else {
+            return Some(relation);
+        };
+
+        self.drop_relation(&relation.from, &relation.to, relation.kind, reason);
+        None
+    }
+
+    pub(super) fn drop_relation(
+        &self,
+        from: &GlobalPosition,
+        to: &GlobalPosition,
+        kind: RelationKind,
+        reason: DropReason,
+    ) {
+        let relation = DroppedRelation {
+            from: path_position(&self.paths, from),
+            to: path_position(&self.paths, to),
+            kind,
+            reason,
+        };
+        self.diagnostics
+            .borrow_mut()
+            .dropped_relations
+            .push(relation);
+    }
+
+    /// Records a call into an external package from a position that is not a symbol of the graph.
+    pub(super) fn drop_external_call(
+        &self,
+        from: &GlobalPosition,
+        item: &CallHierarchyItem,
+        item_path: String,
+    ) {
+        let relation = DroppedRelation {
+            from: path_position(&self.paths, from),
+            to: PathPosition {
+                path: item_path,
+                position: item.selection_range.start,
+            },
+            kind: RelationKind::Call,
+            reason: DropReason::UnknownSource,
+        };
+        self.diagnostics
+            .borrow_mut()
+            .dropped_relations
+            .push(relation);
+    }
+
+    /// Records a call whose item is in a file that has not been added.
+    pub(super) fn unresolved_call(
+        &self,
+        item: &CallHierarchyItem,
+        item_path: String,
+        known: &GlobalPosition,
+        incoming: bool,
+    ) {
+        let unresolved = PathPosition {
+            path: item_path,
+            position: item.selection_range.start,
+        };
+        let known = path_position(&self.paths, known);
+        let (from, to) = if incoming {
+            (unresolved, known)
+        } else {
+            (known, unresolved)
+        };
+
+        self.diagnostics
+            .borrow_mut()
+            .unresolved_calls
+            .push(UnresolvedCall {
+                from,
+                to,
+                name: item.name.clone(),
+            });
+    }
+
+    pub(super) fn finish(self) -> Diagnostics {
+        let mut diagnostics = self.diagnostics.into_inner();
+        diagnostics.unresolved_calls.sort();
+        diagnostics.unresolved_calls.dedup();
+        diagnostics.dropped_relations.sort();
+        diagnostics.dropped_relations.dedup();
+
+        diagnostics
+    }
+}
@@ -0,0 +1,146 @@
+use {
+    super::GraphGenerator,
+    crate::{
+        lang::Language,
+        types::{
+            graph::{ExternalPackage, File, GlobalPosition, Symbol},
+            lsp::CallHierarchyItem,
+        },
+    },
+    std::{
+        cell::RefCell,
+        collections::{BTreeMap, HashMap},
+    },
+};
+
+impl GraphGenerator {
+    /// Keeps the calls to files outside of the analyzed files and the workspace roots,
+    /// grouping the called symbols by library or package.
+    pub fn set_keep_external_calls(&mut self, keep: bool) {
+        self.keep_external_calls = keep;
+    }
+
+    pub(super) fn is_external(&self, path: &str) -> bool {
+        self.keep_external_calls && self.file_root(path).0.is_none()
+    }
+}
+
+/// Collects the symbols of external files called while generating a graph.
+pub(super) struct Externals<'a> {
+    /// Ids of external files follow the ids of all files known to the generator.
+    first_id: u32,
+    files: RefCell<BTreeMap<String, (u32, Vec<&'a CallHierarchyItem>)>>,
+}
+
+/// The external files grouped by package, along with the ids of the files in the graph
+/// and the ids of the symbols.
+pub(super) type FinishedExternals = (
+    Vec<ExternalPackage>,
+    HashMap<u32, u32>,
+    HashMap<GlobalPosition, String>,
+);
+
+impl<'a> Externals<'a> {
+    pub(super) fn new(first_id: u32) -> Self {
+        Self {
+            first_id,
+            files: RefCell::new(BTreeMap::new()),
+        }
+    }
+
+    pub(super) fn add(&self, path: String, item: &'a CallHierarchyItem) -> GlobalPosition {
+        let mut files = self.files.borrow_mut();
+        let next_id = self.first_id + files.len() as u32;
+        let (id, items) = files.entry(path).or_insert_with(|| (next_id, vec![]));
+        items.push(item);
+
+        GlobalPosition::new(*id, item.selection_range.start)
+    }
+
+    /// Numbers the files after the `file_count` analyzed files, in the order of packages and paths.
+    pub(super) fn finish(self, lang: &dyn Language, file_count: u32) -> FinishedExternals {
+        let files = self.files.into_inner();
+
+        let mut packages = BTreeMap::<_, Vec<_>>::new();
+        for (path, file) in &files {
+            packages
+                .entry(lang.external_package(path))
+                .or_default()
+                .push((path, file));
+        }
+
+        let mut file_ids = HashMap::new();
+        let mut symbol_ids = HashMap::new();
+
+        let packages = packages
+            .into_iter()
+            .map(|((name, root), files)| {
+                let files = files
+                    .into_iter()
+                    .map(|(path, (id, items))| {
+                        let file_id = file_count + file_ids.len() as u32 + 1;
+                        file_ids.insert(*id, file_id);
+
+                        let mut items = items.clone();
+                        items.sort_by_key(|item| item.selection_range.start);
+                        items.dedup_by_key(|item| item.selection_range.start);
+
+                        // ids name the package rather than its location, like workspace paths
+                        let relative_path = path
+                            .strip_prefix(root)
+                            .map_or(path.as_str(), |p| p.trim_start_matches('/'))
+                            .to_string();
+                        let package_path = File::make_workspace_path(Some(name), &relative_path);
+
+                        let mut symbols = Vec::<Symbol>::new();
+                        for item in items {
+                            let symbol_id = Symbol::make_top_level_id(
+                                &package_path,
+                                item.kind,
+                                &item.name,
+                                |id| {
+                                    symbols
+                                        .iter()
+                                        .filter(|symbol| Symbol::is_overload_of(&symbol.id, id))
+                                        .count() as u32
+                                },
+                            );
+                            symbol_ids.insert(
+                                GlobalPosition::new(*id, item.selection_range.start),
+                                symbol_id.clone(),
+                            );
+
+                            symbols.push(Symbol {
+                                id: symbol_id,
+                                name: item.name.clone(),
+                                qualified_name: lang.qualified_name(path, None, &item.name),
+                                detail: item.detail.clone(),
+                                kind: item.kind,
+                                tags: item.tags.clone().unwrap_or_default(),
+                                range: item.range,
+                                selection_range: item.selection_range,
+                                children: vec![],
+                            });
+                        }
+
+                        File {
+                            id: file_id,
+                            path: path.clone(),
+                            root: None,
+                            relative_path,
+                            symbols,
+                        }
+                    })
+                    .collect();
+
+                ExternalPackage {
+                    name: name.to_string(),
+                    path: root.to_string(),
+                    files,
+                }
+            })
+            .collect();
+
+        (packages, file_ids, symbol_ids)
+    }
+}
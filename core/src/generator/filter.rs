@@ -0,0 +1,179 @@
+use {
+    super::GraphGenerator,
+    crate::{
+        lang::Language,
+        types::lsp::{DocumentSymbol, SymbolKind},
+    },
+    serde::{de::Error as _, Deserialize, Deserializer},
+};
+
+/// Rules deciding which files and symbols end up in the graph.
+///
+/// Names and paths are matched with glob patterns, where `?` matches a character, `*` any
+/// characters but `/`, and `**` any characters. Paths are matched both in full and relative to
+/// their workspace root. Exclusions take precedence over inclusions, which in turn keep symbols
+/// the rules of the language would drop.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct SymbolFilter {
+    /// Whether to apply the rules of the language, such as dropping variables and constants.
+    pub language_defaults: bool,
+    #[serde(deserialize_with = "kind_names")]
+    pub include_kinds: Vec<SymbolKind>,
+    #[serde(deserialize_with = "kind_names")]
+    pub exclude_kinds: Vec<SymbolKind>,
+    pub include_names: Vec<String>,
+    pub exclude_names: Vec<String>,
+    /// When not empty, only files matching one of the patterns are added.
+    pub include_paths: Vec<String>,
+    pub exclude_paths: Vec<String>,
+}
+
+impl Default for SymbolFilter {
+    fn default() -> Self {
+        Self {
+            language_defaults: true,
+            include_kinds: vec![],
+            exclude_kinds: vec![],
+            include_names: vec![],
+            exclude_names: vec![],
+            include_paths: vec![],
+            exclude_paths: vec![],
+        }
+    }
+}
+
+impl SymbolFilter {
+    /// A filter keeping every file and symbol.
+    pub fn none() -> Self {
+        Self {
+            language_defaults: false,
+            ..Self::default()
+        }
+    }
+
+    pub(super) fn keeps_symbol(
+        &self,
+        lang: &dyn Language,
+        symbol: &DocumentSymbol,
+        parent: Option<&DocumentSymbol>,
+    ) -> bool {
+        let matches_name = |patterns: &[String]| {
+            patterns
+                .iter()
+                .any(|pattern| glob_match(pattern, &symbol.name))
+        };
+
+        if self.exclude_kinds.contains(&symbol.kind) || matches_name(&self.exclude_names) {
+            false
+        } else if self.include_kinds.contains(&symbol.kind) || matches_name(&self.include_names) {
+            true
+        } else {
+            !self.language_defaults || lang.filter_symbol(symbol, parent)
+        }
+    }
+
+    fn keeps_file(&self, paths: [&str; 2]) -> bool {
+        let matches_path = |patterns: &[String]| {
+            patterns
+                .iter()
+                .any(|pattern| paths.iter().any(|path| glob_match(pattern, path)))
+        };
+
+        (self.include_paths.is_empty() || matches_path(&self.include_paths))
+            && !matches_path(&self.exclude_paths)
+    }
+}
+
+impl GraphGenerator {
+    /// Whether the file is dropped by the rules of the language or the path patterns of the filter.
+    pub(super) fn is_filtered_out(&self, path: &str) -> bool {
+        let (_, relative) = self.file_root(path);
+        self.lang.should_filter_out_file(path) || !self.filter.keeps_file([path, &relative])
+    }
+}
+
+fn kind_names<'de, D>(deserializer: D) -> Result<Vec<SymbolKind>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    Vec::<String>::deserialize(deserializer)?
+        .iter()
+        .map(|name| name.parse().map_err(D::Error::custom))
+        .collect()
+}
+
+/// Matches the pattern token by token, tracking every position of the text the pattern so far
+/// can end at, which takes time proportional to the product of the lengths rather than backtracking.
+fn glob_match(pattern: &str, text: &str) -> bool {
+    let text = text.chars().collect::<Vec<_>>();
+    let mut ends = vec![false; text.len() + 1];
+    ends[0] = true;
+
+    for token in tokens(pattern) {
+        let mut next = vec![false; text.len() + 1];
+        let mut reached = false;
+
+        for j in 0..=text.len() {
+            next[j] = match token {
+                Token::Char(c) => j > 0 && ends[j - 1] && text[j - 1] == c,
+                Token::One => j > 0 && ends[j - 1] && text[j - 1] != '/',
+                Token::Star => {
+                    // a `*` does not cross directories
+                    if j > 0 && text[j - 1] == '/' {
+                        reached = false;
+                    }
+                    reached |= ends[j];
+                    reached
+                }
+                Token::AnyStar => {
+                    reached |= ends[j];
+                    reached
+                }
+                Token::Dirs => {
+                    // any directories, each ending with `/`, or none
+                    let dirs = reached && text[j - 1] == '/';
+                    reached |= ends[j];
+                    ends[j] || dirs
+                }
+            };
+        }
+
+        ends = next;
+    }
+
+    ends[text.len()]
+}
+
+#[derive(Clone, Copy)]
+enum Token {
+    Char(char),
+    /// `?`
+    One,
+    /// `*`
+    Star,
+    /// `**`
+    AnyStar,
+    /// `**/`
+    Dirs,
+}
+
+fn tokens(pattern: &str) -> Vec<Token> {
+    let mut chars = pattern.chars().peekable();
+    let mut tokens = vec![];
+
+    while let Some(c) = chars.next() {
+        let token = match c {
+            '?' => Token::One,
+            '*' if chars.next_if_eq(&'*').is_some() => match chars.next_if_eq(&'/') {
+                Some(_) => Token::Dirs,
+                None => Token::AnyStar,
+            },
+            '*' => Token::Star,
+            c => Token::Char(c),
+        };
+        tokens.push(token);
+    }
+
+    tokens
+}
@@ -0,0 +1,113 @@
+use {
+    super::GraphGenerator,
+    crate::types::lsp::{
+        DocumentSymbol, DocumentSymbolResponse, Range, SymbolInformation, SymbolTag,
+    },
+    std::collections::HashMap,
+};
+
+impl GraphGenerator {
+    /// Adds a file from flat symbol information, as returned by servers without hierarchical
+    /// document symbol support, rebuilding the hierarchy first.
+    pub fn add_flat_file(&mut self, path: String, symbols: Vec<SymbolInformation>) -> bool {
+        self.add_file(path, nest_symbols(symbols))
+    }
+}
+
+impl From<DocumentSymbolResponse> for Vec<DocumentSymbol> {
+    fn from(response: DocumentSymbolResponse) -> Self {
+        match response {
+            DocumentSymbolResponse::Nested(symbols) => symbols,
+            DocumentSymbolResponse::Flat(symbols) => nest_symbols(symbols),
+        }
+    }
+}
+
+/// Nests every symbol under the innermost symbol enclosing it, preferring an enclosing symbol
+/// named after its `containerName` when there is one. As some servers only report the range
+/// of the name, a symbol is otherwise nested under the closest preceding symbol of that name.
+fn nest_symbols(mut symbols: Vec<SymbolInformation>) -> Vec<DocumentSymbol> {
+    symbols.sort_by(|a, b| {
+        let (a, b) = (a.location.range, b.location.range);
+        a.start.cmp(&b.start).then(b.end.cmp(&a.end))
+    });
+
+    let mut children = vec![vec![]; symbols.len()];
+    let mut roots = vec![];
+    let mut enclosing: Vec<usize> = vec![];
+    let mut last_named = HashMap::<&str, usize>::new();
+
+    for (i, symbol) in symbols.iter().enumerate() {
+        while enclosing
+            .last()
+            .is_some_and(|&j| !contains(symbols[j].location.range, symbol.location.range))
+        {
+            enclosing.pop();
+        }
+
+        let container = symbol.container_name.as_deref();
+        let parent = container
+            .and_then(|name| {
+                enclosing
+                    .iter()
+                    .rev()
+                    .copied()
+                    .find(|&j| symbols[j].name == name)
+                    .or_else(|| last_named.get(name).copied())
+            })
+            .or(enclosing.last().copied());
+
+        match parent {
+            Some(j) => children[j].push(i),
+            None => roots.push(i),
+        }
+        enclosing.push(i);
+        last_named.insert(&symbol.name, i);
+    }
+
+    let mut symbols = symbols.into_iter().map(Some).collect::<Vec<_>>();
+    roots
+        .into_iter()
+        .map(|i| build_symbol(i, &mut symbols, &children))
+        .collect()
+}
+
+fn build_symbol(
+    i: usize,
+    symbols: &mut [Option<SymbolInformation>],
+    children: &[Vec<usize>],
+) -> DocumentSymbol {
+    let symbol = symbols[i].take().unwrap();
+
+    let mut tags = symbol.tags.unwrap_or_default();
+    if symbol.deprecated == Some(true) && !tags.contains(&SymbolTag::Deprecated) {
+        tags.push(SymbolTag::Deprecated);
+    }
+
+    let children = children[i]
+        .iter()
+        .map(|&child| build_symbol(child, symbols, children))
+        .collect::<Vec<DocumentSymbol>>();
+
+    // children nested by `containerName` may lie outside of the reported range
+    let mut range = symbol.location.range;
+    for child in &children {
+        range.start = range.start.min(child.range.start);
+        range.end = range.end.max(child.range.end);
+    }
+
+    DocumentSymbol {
+        name: symbol.name,
+        detail: None,
+        kind: symbol.kind,
+        tags: (!tags.is_empty()).then_some(tags),
+        range,
+        // the name of the symbol is not known, so this is the whole reported range
+        selection_range: symbol.location.range,
+        children,
+    }
+}
+
+fn contains(outer: Range, inner: Range) -> bool {
+    outer.start <= inner.start && inner.end <= outer.end
+}
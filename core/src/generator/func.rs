@@ -0,0 +1,149 @@
+use {
+    super::GraphGenerator,
+    crate::types::{
+        graph::{File, GlobalPosition, Graph, Relation, RelationKind, Symbol},
+        lsp::{Position, Uri},
+    },
+    serde_repr::Deserialize_repr,
+    std::collections::{HashMap, HashSet, VecDeque},
+};
+
+/// The values match the flags used by the editor extension.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize_repr)]
+#[repr(u8)]
+pub enum FuncCallDirection {
+    Incoming = 1 << 1,
+    Outgoing = 1 << 2,
+    Bidirection = 1 << 1 | 1 << 2,
+}
+
+impl FuncCallDirection {
+    fn has(self, direction: FuncCallDirection) -> bool {
+        self as u8 & direction as u8 == direction as u8
+    }
+}
+
+impl GraphGenerator {
+    /// Generates a call graph that only contains the functions within `max_depth` calls of the root function,
+    /// following callers, callees or both. `None` means there is no limit on the depth.
+    pub fn gen_func_graph(
+        &self,
+        path: &str,
+        position: Position,
+        max_depth: Option<u32>,
+        direction: FuncCallDirection,
+    ) -> Graph {
+        let mut graph = self.gen_graph();
+        let path = self.resolve_path(&Uri::parse(path));
+
+        let Some(root) = graph
+            .files
+            .iter()
+            .find(|file| file.path == path)
+            .and_then(|file| {
+                let symbol = root_symbol(file, position)?;
+                Some(GlobalPosition::new(file.id, symbol.selection_range.start))
+            })
+        else {
+            return Graph {
+                roots: vec![],
+                files: vec![],
+                externals: vec![],
+                relations: vec![],
+            };
+        };
+
+        let mut reached = HashSet::from([root]);
+        if direction.has(FuncCallDirection::Incoming) {
+            reached.extend(reachable(&graph.relations, root, max_depth, true));
+        }
+        if direction.has(FuncCallDirection::Outgoing) {
+            reached.extend(reachable(&graph.relations, root, max_depth, false));
+        }
+
+        graph.files.retain_mut(|file| {
+            retain_symbols(file.id, &mut file.symbols, &reached);
+            !file.symbols.is_empty()
+        });
+        graph.externals.retain_mut(|package| {
+            package.files.retain_mut(|file| {
+                retain_symbols(file.id, &mut file.symbols, &reached);
+                !file.symbols.is_empty()
+            });
+            !package.files.is_empty()
+        });
+        graph.roots = self.used_roots(&graph.files);
+        graph
+            .relations
+            .retain(|relation| reached.contains(&relation.from) && reached.contains(&relation.to));
+
+        graph
+    }
+}
+
+/// Finds the symbol the root position points at, which may not be the start of its name,
+/// e.g. the `fn` keyword: the innermost symbol containing it, or else the only symbol on its line.
+fn root_symbol(file: &File, position: Position) -> Option<&Symbol> {
+    file.symbol_at(position).or_else(|| {
+        let mut stack = file.symbols.iter().collect::<Vec<_>>();
+        let mut on_line = vec![];
+        while let Some(symbol) = stack.pop() {
+            stack.extend(&symbol.children);
+            if symbol.selection_range.start.line == position.line {
+                on_line.push(symbol);
+            }
+        }
+
+        match on_line[..] {
+            [symbol] => Some(symbol),
+            _ => None,
+        }
+    })
+}
+
+fn reachable(
+    relations: &[Relation],
+    root: GlobalPosition,
+    max_depth: Option<u32>,
+    incoming: bool,
+) -> HashSet<GlobalPosition> {
+    let mut adjacency = HashMap::<GlobalPosition, Vec<GlobalPosition>>::new();
+    for relation in relations
+        .iter()
+        .filter(|relation| matches!(relation.kind, RelationKind::Call))
+    {
+        let (from, to) = if incoming {
+            (relation.to, relation.from)
+        } else {
+            (relation.from, relation.to)
+        };
+        adjacency.entry(from).or_default().push(to);
+    }
+
+    let mut visited = HashSet::from([root]);
+    let mut queue = VecDeque::from([(root, 0)]);
+
+    while let Some((node, depth)) = queue.pop_front() {
+        if max_depth.is_some_and(|max_depth| depth >= max_depth) {
+            continue;
+        }
+
+        for next in adjacency.get(&node).into_iter().flatten() {
+            if visited.insert(*next) {
+                queue.push_back((*next, depth + 1));
+            }
+        }
+    }
+
+    visited
+}
+
+/// Keeps the reached symbols and the symbols containing them.
+fn retain_symbols(file_id: u32, symbols: &mut Vec<Symbol>, reached: &HashSet<GlobalPosition>) {
+    symbols.retain_mut(|symbol| {
+        retain_symbols(file_id, &mut symbol.children, reached);
+
+        !symbol.children.is_empty()
+            || reached.contains(&GlobalPosition::new(file_id, symbol.selection_range.start))
+    });
+}
@@ -3,53 +3,117 @@ mod wasm;
 #[cfg(feature = "wasm")]
 pub use wasm::{set_panic_hook, GraphGeneratorWasm};
 
+mod aggregate;
+mod cache;
+mod diagnostics;
+mod external;
+mod filter;
+mod flat;
+mod func;
+mod resolve;
+mod roots;
 #[cfg(test)]
 mod tests;
 
+pub use {filter::SymbolFilter, func::FuncCallDirection};
+
 use {
     crate::{
+        analysis::{self, UnusedSymbol},
         lang,
         types::{
-            graph::{File, GlobalPosition, Graph, Relation, RelationKind, Symbol},
+            graph::{
+                Diagnostics, DropReason, File, GlobalPosition, Graph, PathPosition, RejectReason,
+                RejectedFile, Relation, RelationKind, Symbol, WorkspaceRoot,
+            },
             lsp::{
                 CallHierarchyIncomingCall, CallHierarchyItem, CallHierarchyOutgoingCall,
-                DocumentSymbol, Location, Position, SymbolKind,
+                DocumentSymbol, Location, Position, Range, SymbolKind, TypeHierarchyItem, Uri,
             },
         },
     },
+    diagnostics::Collector,
+    external::Externals,
     std::{
         cell::RefCell,
         collections::{hash_map::Entry, HashMap, HashSet},
     },
 };
 
+fn path_position(paths: &HashMap<u32, &String>, position: &GlobalPosition) -> PathPosition {
+    PathPosition {
+        path: paths[&position.file_id].clone(),
+        position: Position {
+            line: position.line,
+            character: position.character,
+        },
+    }
+}
+
+/// State shared by the symbols of a file while converting them.
+struct SymbolContext<'a> {
+    file_id: u32,
+    path: &'a str,
+    /// Prefix of the ids of top-level symbols.
+    workspace_path: &'a str,
+    overloads: HashMap<String, u32>,
+    all_symbols: &'a mut HashMap<GlobalPosition, String>,
+}
+
+#[derive(Clone, Copy)]
+struct Container<'a> {
+    symbol: &'a DocumentSymbol,
+    id: &'a str,
+    qualified_name: &'a str,
+}
+
 pub struct GraphGenerator {
     lang: Box<dyn lang::Language>,
 
+    /// The path of every file, keyed by [`Uri::key`], so that each file keeps the spelling it was first seen with.
+    paths: HashMap<String, String>,
+    roots: Vec<WorkspaceRoot>,
     file_id_map: HashMap<String, u32>,
     files: HashMap<String, Vec<DocumentSymbol>>,
+    rejected_files: HashMap<String, RejectReason>,
+    file_hashes: HashMap<String, String>,
     incoming_calls: HashMap<GlobalPosition, Vec<CallHierarchyIncomingCall>>,
     outgoing_calls: HashMap<GlobalPosition, Vec<CallHierarchyOutgoingCall>>,
     interfaces: HashMap<GlobalPosition, Vec<GlobalPosition>>,
+    supertypes: HashMap<GlobalPosition, Vec<GlobalPosition>>,
+    subtypes: HashMap<GlobalPosition, Vec<GlobalPosition>>,
 
-    filter: bool,
+    filter: SymbolFilter,
+    keep_external_calls: bool,
 }
 
 impl GraphGenerator {
-    pub fn new(lang: &str, filter: bool) -> Self {
+    pub fn new(lang: &str, filter: SymbolFilter) -> Self {
         Self {
             lang: lang::language_handler(lang),
 
+            paths: HashMap::new(),
+            roots: Vec::new(),
             file_id_map: HashMap::new(),
             files: HashMap::new(),
+            rejected_files: HashMap::new(),
+            file_hashes: HashMap::new(),
             incoming_calls: HashMap::new(),
             outgoing_calls: HashMap::new(),
             interfaces: HashMap::new(),
+            supertypes: HashMap::new(),
+            subtypes: HashMap::new(),
 
             filter,
+            keep_external_calls: false,
         }
     }
 
+    fn global_position(&mut self, path: &str, position: Position) -> GlobalPosition {
+        let path = self.intern_path(&Uri::parse(path));
+        GlobalPosition::new(self.alloc_file_id(path), position)
+    }
+
     fn alloc_file_id(&mut self, path: String) -> u32 {
         let len = self.file_id_map.len();
         self.file_id_map
@@ -58,12 +122,31 @@ impl GraphGenerator {
             .to_owned()
     }
 
+    /// Returns the path of the file the URI or path refers to, in the spelling used in the graph.
+    fn intern_path(&mut self, uri: &Uri) -> String {
+        self.paths
+            .entry(uri.key())
+            .or_insert_with(|| uri.canonical())
+            .clone()
+    }
+
+    /// Like [`intern_path`](Self::intern_path), without recording the spelling of files not seen yet.
+    fn resolve_path(&self, uri: &Uri) -> String {
+        self.paths
+            .get(&uri.key())
+            .cloned()
+            .unwrap_or_else(|| uri.canonical())
+    }
+
     pub fn should_filter_out_file(&self, path: &str) -> bool {
-        self.lang.should_filter_out_file(path)
+        self.is_filtered_out(&self.resolve_path(&Uri::parse(path)))
     }
 
+    /// Returns `false` if the file is rejected, with the reason listed in the diagnostics of the graph.
     pub fn add_file(&mut self, path: String, symbols: Vec<DocumentSymbol>) -> bool {
-        if self.lang.should_filter_out_file(&path) {
+        let path = self.intern_path(&Uri::parse(&path));
+        if self.is_filtered_out(&path) {
+            self.rejected_files.insert(path, RejectReason::FilteredOut);
             return false;
         }
 
@@ -71,12 +154,50 @@ impl GraphGenerator {
             Entry::Vacant(entry) => {
                 let key = entry.key().clone();
                 entry.insert(symbols);
+                self.rejected_files.remove(&key);
                 self.alloc_file_id(key);
             }
-            Entry::Occupied(_) => return false,
+            Entry::Occupied(entry) => {
+                let path = entry.key().clone();
+                self.rejected_files.insert(path, RejectReason::Duplicate);
+                return false;
+            }
         }
 
-        return true;
+        true
+    }
+
+    /// Drops the symbols of the file along with every call, implementation and type hierarchy entry keyed on it.
+    /// Returns `false` if the file has not been added.
+    pub fn remove_file(&mut self, path: &str) -> bool {
+        let path = self.resolve_path(&Uri::parse(path));
+        self.file_hashes.remove(&path);
+        self.remove_file_entries(&path)
+    }
+
+    /// Removes the symbols of the file and the entries keyed on it, keeping its recorded content hash.
+    fn remove_file_entries(&mut self, path: &str) -> bool {
+        let Some(&id) = self.file_id_map.get(path) else {
+            return false;
+        };
+
+        self.incoming_calls.retain(|callee, _| callee.file_id != id);
+        self.outgoing_calls.retain(|caller, _| caller.file_id != id);
+        self.interfaces
+            .retain(|interface, _| interface.file_id != id);
+        self.supertypes.retain(|subtype, _| subtype.file_id != id);
+        self.subtypes.retain(|supertype, _| supertype.file_id != id);
+
+        self.files.remove(path).is_some()
+    }
+
+    /// Replaces the symbols of an already added file, dropping the entries keyed on it,
+    /// so that only the changed files have to be requested again.
+    /// The content recorded with [`record_file_content`](Self::record_file_content) is kept.
+    pub fn update_file(&mut self, path: String, symbols: Vec<DocumentSymbol>) -> bool {
+        let resolved = self.resolve_path(&Uri::parse(&path));
+        self.remove_file_entries(&resolved);
+        self.add_file(path, symbols)
     }
 
     // TODO: graph database
@@ -86,7 +207,7 @@ impl GraphGenerator {
         position: Position,
         calls: Vec<CallHierarchyIncomingCall>,
     ) {
-        let location = GlobalPosition::new(self.alloc_file_id(path), position);
+        let location = self.global_position(&path, position);
         self.incoming_calls.insert(location, calls);
     }
 
@@ -96,7 +217,7 @@ impl GraphGenerator {
         position: Position,
         calls: Vec<CallHierarchyOutgoingCall>,
     ) {
-        let location = GlobalPosition::new(self.alloc_file_id(path), position);
+        let location = self.global_position(&path, position);
         self.outgoing_calls.insert(location, calls);
     }
 
@@ -106,215 +227,528 @@ impl GraphGenerator {
         position: Position,
         locations: Vec<Location>,
     ) {
-        let location = GlobalPosition::new(self.alloc_file_id(path), position);
+        let location = self.global_position(&path, position);
         let implementations = locations
             .into_iter()
             .map(|location| {
-                GlobalPosition::new(self.alloc_file_id(location.uri.path), location.range.start)
+                let path = self.intern_path(&location.uri);
+                GlobalPosition::new(self.alloc_file_id(path), location.range.start)
             })
             .collect();
         self.interfaces.insert(location, implementations);
     }
 
+    pub fn add_type_hierarchy_supertypes(
+        &mut self,
+        path: String,
+        position: Position,
+        items: Vec<TypeHierarchyItem>,
+    ) {
+        let location = self.global_position(&path, position);
+        let supertypes = self.type_items_global_locations(items);
+        self.supertypes.insert(location, supertypes);
+    }
+
+    pub fn add_type_hierarchy_subtypes(
+        &mut self,
+        path: String,
+        position: Position,
+        items: Vec<TypeHierarchyItem>,
+    ) {
+        let location = self.global_position(&path, position);
+        let subtypes = self.type_items_global_locations(items);
+        self.subtypes.insert(location, subtypes);
+    }
+
+    fn type_items_global_locations(
+        &mut self,
+        items: Vec<TypeHierarchyItem>,
+    ) -> Vec<GlobalPosition> {
+        items
+            .into_iter()
+            .filter(|item| {
+                matches!(
+                    item.kind,
+                    SymbolKind::Class | SymbolKind::Struct | SymbolKind::Interface
+                )
+            })
+            .map(|item| {
+                let path = self.intern_path(&item.uri);
+                GlobalPosition::new(self.alloc_file_id(path), item.selection_range.start)
+            })
+            .collect()
+    }
+
     pub fn gen_graph(&self) -> Graph {
-        let (files, symbols) = self.collect_files_and_symbols();
+        self.gen_graph_with_diagnostics().0
+    }
+
+    /// Generates the graph along with the reasons files and relations are left out of it.
+    pub fn gen_graph_with_diagnostics(&self) -> (Graph, Diagnostics) {
+        let file_ids = self.sorted_file_ids();
+        let (files, symbols) = self.collect_files_and_symbols(&file_ids);
+        let files = RefCell::new(files);
         let files_ref = &files;
         let symbols_ref = &symbols;
+        let file_ids_ref = &file_ids;
+
+        let paths = self.file_paths();
+
+        // nested symbols are inserted while walking incoming calls, so walk them in a fixed order
+        let mut incoming_calls = self.incoming_calls.iter().collect::<Vec<_>>();
+        incoming_calls.sort_by_cached_key(|(callee, _)| path_position(&paths, callee));
+
+        let resolve = |position: &GlobalPosition, name: Option<&str>| {
+            self.resolve_symbol(paths[&position.file_id], *position, name, symbols_ref)
+        };
+        let resolve_ref = &resolve;
 
-        let inserted_symbols = RefCell::new(HashSet::new());
+        let collector = Collector::new(paths.clone());
+        let collector_ref = &collector;
+
+        let externals = Externals::new(self.file_id_map.len() as u32 + 1);
+        let externals_ref = &externals;
+
+        let inserted_symbols = RefCell::new(HashMap::new());
         let inserted_symbols_ref = &inserted_symbols;
 
-        let incoming_calls = self
-            .incoming_calls
-            .iter()
-            .filter_map(|(callee, callers)| symbols.contains(&callee).then_some((callee, callers)))
-            .flat_map(|(to, calls)| {
-                calls.into_iter().filter_map(move |call| {
-                    let from = self.call_item_global_location(&call.from)?;
-
-                    // incoming calls may start from nested functions, which may not be included in file symbols in some lsp server implementations.
-                    // in that case, we add the missing nested symbol to the symbol list.
-                    // another approach would be to modify edges to make them start from the outter functions, which is not so accurate
-
-                    (symbols_ref.contains(&from)
-                        || inserted_symbols_ref.borrow().contains(&from)
-                        || {
-                            let id = *self.file_id_map.get(&call.from.uri.path)?;
-                            let node = files_ref.get(id as usize - 1)? as *const File;
-
-                            let updated = self.try_insert_symbol(&call.from, unsafe {
-                                node.cast_mut().as_mut().unwrap()
-                            });
-
-                            if updated {
-                                inserted_symbols_ref.borrow_mut().insert(from);
-                            }
-                            updated
-                        })
-                    .then_some(Relation {
-                        from,
-                        to: to.to_owned(),
-                        kind: RelationKind::Call,
-                    })
-                })
-            });
-
-        let outgoing_calls = self
-            .outgoing_calls
-            .iter()
-            .filter_map(|(caller, callees)| {
-                symbols_ref.contains(&caller).then_some((caller, callees))
+        let incoming_calls = incoming_calls.into_iter().flat_map(|(to, calls)| {
+            let to = resolve(to, None);
+
+            calls.iter().filter_map(move |call| {
+                let to = &to;
+                let Some(from) = self.call_item_global_location(&call.from) else {
+                    let path = self.resolve_path(&call.from.uri);
+                    collector_ref.unresolved_call(&call.from, path, to, true);
+                    return None;
+                };
+                let from = resolve_ref(&from, Some(&call.from.name));
+                if !symbols_ref.contains_key(to) {
+                    collector_ref.drop_relation(
+                        &from,
+                        to,
+                        RelationKind::Call,
+                        DropReason::UnknownTarget,
+                    );
+                    return None;
+                }
+
+                // incoming calls may start from nested functions, which may not be included in file symbols in some lsp server implementations.
+                // in that case, we add the missing nested symbol to the symbol list.
+                // another approach would be to modify edges to make them start from the outter functions, which is not so accurate
+
+                let known = symbols_ref.contains_key(&from)
+                    || inserted_symbols_ref.borrow().contains_key(&from)
+                    || {
+                        let id = file_ids_ref[&from.file_id];
+                        let file = &mut files_ref.borrow_mut()[id as usize - 1];
+
+                        let inserted = self.try_insert_symbol(&call.from, file);
+
+                        if let Some(symbol_id) = &inserted {
+                            inserted_symbols_ref
+                                .borrow_mut()
+                                .insert(from, symbol_id.clone());
+                        }
+                        inserted.is_some()
+                    };
+                if !known {
+                    collector_ref.drop_relation(
+                        &from,
+                        to,
+                        RelationKind::Call,
+                        DropReason::UnknownSource,
+                    );
+                    return None;
+                }
+
+                Some(Relation::new(
+                    from,
+                    to.to_owned(),
+                    RelationKind::Call,
+                    call.from_ranges.clone(),
+                ))
             })
-            .flat_map(|(from, callees)| {
-                callees.into_iter().filter_map(move |call| {
-                    let to = self.call_item_global_location(&call.to)?;
+        });
+
+        let outgoing_calls = self.outgoing_calls.iter().flat_map(|(from, callees)| {
+            let from = resolve(from, None);
+
+            callees.iter().filter_map(move |call| {
+                let from = &from;
+                let Some(to) = self.call_item_global_location(&call.to) else {
+                    let path = self.resolve_path(&call.to.uri);
+                    if self.is_external(&path) {
+                        // the package would otherwise be listed with a call from nowhere
+                        if !symbols_ref.contains_key(from) {
+                            collector_ref.drop_external_call(from, &call.to, path);
+                            return None;
+                        }
+                        return Some(Relation::new(
+                            from.to_owned(),
+                            externals_ref.add(path, &call.to),
+                            RelationKind::Call,
+                            call.from_ranges.clone(),
+                        ));
+                    }
+
+                    collector_ref.unresolved_call(&call.to, path, from, false);
+                    return None;
+                };
+                let to = resolve_ref(&to, Some(&call.to.name));
 
-                    symbols_ref.contains(&to).then_some(Relation {
-                        from: from.to_owned(),
+                collector_ref.check(
+                    symbols_ref,
+                    Relation::new(
+                        from.to_owned(),
                         to,
-                        kind: RelationKind::Call,
-                    })
-                })
-            });
+                        RelationKind::Call,
+                        call.from_ranges.clone(),
+                    ),
+                )
+            })
+        });
+
+        let implementations = self.interfaces.iter().flat_map(|(to, implementations)| {
+            implementations.iter().map(move |location| {
+                Relation::new(
+                    resolve_ref(location, None),
+                    resolve_ref(to, None),
+                    RelationKind::Impl,
+                    vec![],
+                )
+            })
+        });
+
+        let supertypes = self.supertypes.iter().flat_map(|(from, supertypes)| {
+            supertypes.iter().map(move |to| {
+                Relation::new(
+                    resolve_ref(from, None),
+                    resolve_ref(to, None),
+                    RelationKind::Inherit,
+                    vec![],
+                )
+            })
+        });
+
+        let subtypes = self.subtypes.iter().flat_map(|(to, subtypes)| {
+            subtypes.iter().map(move |from| {
+                Relation::new(
+                    resolve_ref(from, None),
+                    resolve_ref(to, None),
+                    RelationKind::Inherit,
+                    vec![],
+                )
+            })
+        });
+
+        let type_relations = implementations
+            .chain(supertypes)
+            .chain(subtypes)
+            .filter_map(|relation| collector_ref.check(symbols_ref, relation));
+
+        // the same call may be reported by both incoming and outgoing calls,
+        // so call sites of duplicate edges are merged rather than dropped,
+        // while relations of different kinds between the same symbols are kept apart
+        let mut edges = HashMap::<(GlobalPosition, GlobalPosition, RelationKind), Relation>::new();
+        for relation in incoming_calls.chain(outgoing_calls).chain(type_relations) {
+            match edges.entry((relation.from, relation.to, relation.kind)) {
+                Entry::Vacant(entry) => {
+                    entry.insert(relation);
+                }
+                Entry::Occupied(mut entry) => entry.get_mut().ranges.extend(relation.ranges),
+            }
+        }
 
-        let implementations = self
-            .interfaces
+        let files = files.into_inner();
+        let (externals, external_file_ids, external_symbols) =
+            externals.finish(self.lang.as_ref(), files.len() as u32);
+        let mut file_ids = file_ids;
+        file_ids.extend(external_file_ids);
+
+        let inserted_symbols = inserted_symbols.into_inner();
+        let symbol_id = |position: &GlobalPosition| {
+            symbols
+                .get(position)
+                .or_else(|| inserted_symbols.get(position))
+                .or_else(|| external_symbols.get(position))
+                .cloned()
+                .unwrap_or_default()
+        };
+
+        let mut relations = edges
+            .into_values()
+            .map(|mut relation| {
+                relation.ranges.sort();
+                relation.ranges.dedup();
+
+                Relation {
+                    from: relation.from.with_file_id(file_ids[&relation.from.file_id]),
+                    to: relation.to.with_file_id(file_ids[&relation.to.file_id]),
+                    from_id: symbol_id(&relation.from),
+                    to_id: symbol_id(&relation.to),
+                    count: relation.ranges.len() as u32,
+                    ..relation
+                }
+            })
+            .collect::<Vec<_>>();
+        relations.sort();
+
+        let mut diagnostics = collector.finish();
+        diagnostics.rejected_files = self
+            .rejected_files
             .iter()
-            .filter_map(|(interface, implementations)| {
-                symbols_ref
-                    .contains(&interface)
-                    .then_some((interface, implementations))
+            .map(|(path, reason)| RejectedFile {
+                path: path.clone(),
+                reason: *reason,
             })
-            .flat_map(|(to, implementations)| {
-                implementations.into_iter().filter_map(move |location| {
-                    symbols_ref.contains(location).then_some(Relation {
-                        from: location.to_owned(),
-                        to: to.to_owned(),
-                        kind: RelationKind::Impl,
-                    })
-                })
-            });
-
-        let edges = incoming_calls
-            .chain(outgoing_calls)
-            .chain(implementations)
-            .collect::<HashSet<_>>();
-
-        Graph {
+            .collect();
+        diagnostics.rejected_files.sort();
+
+        let graph = Graph {
+            roots: self.used_roots(&files),
             files,
-            relations: edges.into_iter().collect(),
+            externals,
+            relations,
+        };
+
+        (graph, diagnostics)
+    }
+
+    /// Lists the functions and methods nothing calls, leaving out the entry points of the language.
+    ///
+    /// Language servers report neither visibility nor exports, so the public API of Rust libraries
+    /// and the exported functions of JavaScript and TypeScript modules are listed when nothing
+    /// in the workspace calls them.
+    pub fn find_dead_code(&self) -> Vec<UnusedSymbol> {
+        let graph = self.gen_graph();
+        let file_ids = self.sorted_file_ids();
+
+        let mut entry_points = HashSet::new();
+        for (path, symbols) in &self.files {
+            let id = file_ids[&self.file_id_map[path]];
+            self.collect_entry_points(path, id, symbols, None, &mut entry_points);
         }
+
+        analysis::find_dead_code(&graph, &entry_points)
     }
 
-    fn collect_files_and_symbols(&self) -> (Vec<File>, HashSet<GlobalPosition>) {
-        let mut all_symbols = HashSet::new();
-        let files = self
+    fn collect_entry_points(
+        &self,
+        path: &str,
+        file_id: u32,
+        symbols: &[DocumentSymbol],
+        parent: Option<&DocumentSymbol>,
+        entry_points: &mut HashSet<GlobalPosition>,
+    ) {
+        for symbol in symbols {
+            if self.lang.is_entry_point(path, symbol, parent) {
+                entry_points.insert(GlobalPosition::new(file_id, symbol.selection_range.start));
+            }
+            self.collect_entry_points(path, file_id, &symbol.children, Some(symbol), entry_points);
+        }
+    }
+
+    /// Maps the internal file ids, which depend on the order requests arrive in,
+    /// to the ids used in the generated graph, which follow the order of file paths.
+    fn sorted_file_ids(&self) -> HashMap<u32, u32> {
+        let mut paths = self.files.keys().collect::<Vec<_>>();
+        paths.sort();
+
+        paths
+            .into_iter()
+            .enumerate()
+            .map(|(i, path)| (self.file_id_map[path], i as u32 + 1))
+            .collect()
+    }
+
+    fn collect_files_and_symbols(
+        &self,
+        file_ids: &HashMap<u32, u32>,
+    ) -> (Vec<File>, HashMap<GlobalPosition, String>) {
+        let mut all_symbols = HashMap::new();
+        let mut files = self
             .files
             .iter()
             .map(|(p, symbols)| {
+                let id = self.file_id_map[p];
+                let (root, relative_path) = self.file_root(p);
+                let workspace_path = File::make_workspace_path(root.as_deref(), &relative_path);
+                let mut ctx = SymbolContext {
+                    file_id: id,
+                    path: p,
+                    workspace_path: &workspace_path,
+                    overloads: HashMap::new(),
+                    all_symbols: &mut all_symbols,
+                };
                 let symbols = symbols
                     .iter()
-                    .filter_map(|s| {
-                        self.convert_symbol(self.file_id_map[p], s, None, &mut all_symbols)
-                    })
+                    .filter_map(|s| self.convert_symbol(&mut ctx, s, None))
                     .collect();
 
                 File {
-                    id: self.file_id_map[p],
+                    id: file_ids[&id],
                     path: p.clone(),
+                    root,
+                    relative_path,
                     symbols,
                 }
             })
             .collect::<Vec<_>>();
+        files.sort_by_key(|file| file.id);
 
         (files, all_symbols)
     }
 
     fn convert_symbol(
         &self,
-        file_id: u32,
+        ctx: &mut SymbolContext,
         symbol: &DocumentSymbol,
-        parent: Option<&DocumentSymbol>,
-        all_symbols: &mut HashSet<GlobalPosition>,
+        parent: Option<Container>,
     ) -> Option<Symbol> {
-        if self.filter && !self.lang.filter_symbol(symbol, parent) {
+        if !self
+            .filter
+            .keeps_symbol(self.lang.as_ref(), symbol, parent.map(|p| p.symbol))
+        {
             return Option::None;
         }
 
-        all_symbols.insert(GlobalPosition::new(file_id, symbol.selection_range.start));
-
+        let overloads = &mut ctx.overloads;
+        let overload = |id: &str| {
+            let overload = overloads.entry(id.to_string()).or_default();
+            *overload += 1;
+            *overload - 1
+        };
+        let id = match parent {
+            Some(parent) => Symbol::make_child_id(parent.id, symbol.kind, &symbol.name, overload),
+            None => {
+                Symbol::make_top_level_id(ctx.workspace_path, symbol.kind, &symbol.name, overload)
+            }
+        };
+        let qualified_name =
+            self.lang
+                .qualified_name(ctx.path, parent.map(|p| p.qualified_name), &symbol.name);
+        ctx.all_symbols.insert(
+            GlobalPosition::new(ctx.file_id, symbol.selection_range.start),
+            id.clone(),
+        );
+
+        let container = Container {
+            symbol,
+            id: &id,
+            qualified_name: &qualified_name,
+        };
         let children = symbol
             .children
             .iter()
-            .filter_map(|child| self.convert_symbol(file_id, child, Some(symbol), all_symbols))
+            .filter_map(|child| self.convert_symbol(ctx, child, Some(container)))
             .collect();
 
         Some(Symbol {
-            range: symbol.selection_range,
+            id,
+            qualified_name,
+            detail: symbol.detail.clone(),
+            range: symbol.range,
+            selection_range: symbol.selection_range,
             kind: symbol.kind,
+            tags: symbol.tags.clone().unwrap_or_default(),
             name: symbol.name.clone(),
             children,
         })
     }
 
-    fn try_insert_symbol(&self, item: &CallHierarchyItem, node: &mut File) -> bool {
-        let mut cells = &mut node.symbols;
-        let mut is_subsymbol = false;
-
-        loop {
-            let i = match cells.binary_search_by_key(&item.range.start, |cell| cell.range.start) {
-                Ok(_) => return true, // should be unreachable
-                Err(i) => i,
-            };
-
-            if i > 0 {
-                let cell = cells.get(i - 1).unwrap();
-
-                if cell.range.end > item.range.end {
-                    // we just deal with nested functions here
-                    if !matches!(cell.kind, SymbolKind::Function | SymbolKind::Method) {
-                        return false;
-                    }
-                    is_subsymbol = true;
+    /// Inserts an item missing from the document symbols into the innermost symbol enclosing it,
+    /// moving the symbols it encloses in turn into it. Returns the id of the inserted symbol.
+    fn try_insert_symbol(&self, item: &CallHierarchyItem, file: &mut File) -> Option<String> {
+        self.insert_nested_symbol(item, &file.path, &mut file.symbols, None)
+    }
 
-                    // fight the borrow checker
-                    cells = &mut cells.get_mut(i - 1).unwrap().children;
+    /// `parent` is the id and the qualified name of the symbol containing `symbols`,
+    /// which is `None` at the top level, where symbols are not inserted.
+    fn insert_nested_symbol(
+        &self,
+        item: &CallHierarchyItem,
+        path: &str,
+        symbols: &mut Vec<Symbol>,
+        parent: Option<(&str, &str)>,
+    ) -> Option<String> {
+        let encloses = |outer: &Range, inner: &Range| {
+            outer.start <= inner.start && inner.end <= outer.end && outer != inner
+        };
+
+        if let Some(symbol) = symbols
+            .iter()
+            .find(|symbol| symbol.range.start == item.range.start)
+        {
+            return Some(symbol.id.clone()); // should be unreachable
+        }
 
-                    continue;
-                }
-            }
+        if let Some(container) = symbols
+            .iter_mut()
+            .find(|symbol| encloses(&symbol.range, &item.range))
+        {
+            let id = container.id.clone();
+            let qualified_name = container.qualified_name.clone();
+
+            return self.insert_nested_symbol(
+                item,
+                path,
+                &mut container.children,
+                Some((&id, &qualified_name)),
+            );
+        }
 
-            if is_subsymbol {
-                let mut children = vec![];
+        let (parent_id, parent_name) = parent?;
 
-                if let Some(next_cell) = cells.get(i) {
-                    if next_cell.range.start > item.range.start
-                        && next_cell.range.end < item.range.end
-                    {
-                        let next_cell = cells.remove(i);
-                        children.push(next_cell);
-                    }
-                }
+        let children = symbols
+            .extract_if(.., |symbol| encloses(&item.range, &symbol.range))
+            .collect();
 
-                cells.insert(
-                    i,
-                    Symbol {
-                        name: item.name.clone(),
-                        kind: item.kind,
-                        range: item.selection_range,
-                        children,
-                    },
-                );
-            }
+        let id = Symbol::make_child_id(parent_id, item.kind, &item.name, |id| {
+            symbols
+                .iter()
+                .filter(|symbol| Symbol::is_overload_of(&symbol.id, id))
+                .count() as u32
+        });
+
+        let i = symbols.partition_point(|symbol| symbol.range.start < item.range.start);
+        symbols.insert(
+            i,
+            Symbol {
+                id: id.clone(),
+                qualified_name: self
+                    .lang
+                    .qualified_name(path, Some(parent_name), &item.name),
+                name: item.name.clone(),
+                detail: item.detail.clone(),
+                kind: item.kind,
+                tags: item.tags.clone().unwrap_or_default(),
+                range: item.range,
+                selection_range: item.selection_range,
+                children,
+            },
+        );
 
-            return is_subsymbol;
-        }
+        Some(id)
     }
 
+    /// Returns `None` if the file of the item has not been added.
     fn call_item_global_location(&self, item: &CallHierarchyItem) -> Option<GlobalPosition> {
+        let path = self.resolve_path(&item.uri);
+        if !self.files.contains_key(&path) {
+            return None;
+        }
+
         Some(GlobalPosition::new(
-            *self.file_id_map.get(&item.uri.path)?,
+            self.file_id_map[&path],
             item.selection_range.start,
         ))
     }
+
+    fn file_paths(&self) -> HashMap<u32, &String> {
+        self.file_id_map
+            .iter()
+            .map(|(path, id)| (*id, path))
+            .collect()
+    }
 }
@@ -0,0 +1,74 @@
+use {
+    super::GraphGenerator,
+    crate::types::{
+        graph::GlobalPosition,
+        lsp::{DocumentSymbol, Position},
+    },
+    std::collections::HashMap,
+};
+
+impl GraphGenerator {
+    /// Resolves a position reported by the language server to the position of a symbol of the file,
+    /// as servers do not always agree between requests, e.g. pointing at the `fn` keyword
+    /// instead of the name, or being off by a column.
+    ///
+    /// If the name is known, falls back to the symbols with that name on the same line or containing
+    /// the position, preferring the innermost one. Otherwise, falls back to the symbol on the same line
+    /// only if it is the only one and contains the position, as the position may point at a symbol
+    /// left out of the graph.
+    /// Returns the position unchanged if there is no such symbol.
+    pub(super) fn resolve_symbol(
+        &self,
+        path: &str,
+        position: GlobalPosition,
+        name: Option<&str>,
+        symbols: &HashMap<GlobalPosition, String>,
+    ) -> GlobalPosition {
+        if symbols.contains_key(&position) {
+            return position;
+        }
+        let Some(file) = self.files.get(path) else {
+            return position;
+        };
+
+        let target = Position {
+            line: position.line,
+            character: position.character,
+        };
+        let contains =
+            |symbol: &DocumentSymbol| symbol.range.start <= target && target <= symbol.range.end;
+        let mut candidates = vec![];
+
+        let mut stack = file.iter().collect::<Vec<_>>();
+        while let Some(symbol) = stack.pop() {
+            stack.extend(&symbol.children);
+
+            let key = GlobalPosition::new(position.file_id, symbol.selection_range.start);
+            if !symbols.contains_key(&key) || name.is_some_and(|name| name != symbol.name) {
+                continue;
+            }
+
+            let same_line = symbol.selection_range.start.line == target.line
+                || symbol.range.start.line == target.line;
+            if same_line || name.is_some() && contains(symbol) {
+                candidates.push(symbol);
+            }
+        }
+
+        let best = match (name, &candidates[..]) {
+            (Some(_), _) => candidates.iter().copied().reduce(|best, symbol| {
+                if best.range.start <= symbol.range.start && symbol.range.end <= best.range.end {
+                    symbol
+                } else {
+                    best
+                }
+            }),
+            (None, &[symbol]) => contains(symbol).then_some(symbol),
+            (None, _) => None,
+        };
+
+        best.map_or(position, |symbol| {
+            GlobalPosition::new(position.file_id, symbol.selection_range.start)
+        })
+    }
+}
@@ -0,0 +1,53 @@
+use {
+    super::GraphGenerator,
+    crate::types::{
+        graph::{File, WorkspaceRoot},
+        lsp::Uri,
+    },
+};
+
+impl GraphGenerator {
+    /// Adds a workspace root, or moves an existing root with the same name.
+    /// Files under it are given paths relative to it, and grouped by its name in the graph.
+    pub fn add_root(&mut self, name: String, path: String) {
+        let path = Uri::parse(&path).canonical();
+
+        match self.roots.iter_mut().find(|root| root.name == name) {
+            Some(root) => root.path = path,
+            None => self.roots.push(WorkspaceRoot { name, path }),
+        }
+    }
+
+    /// Returns the name of the innermost root containing the file, and the path relative to it.
+    pub(super) fn file_root(&self, path: &str) -> (Option<String>, String) {
+        let key = Uri::parse(path).key();
+
+        self.roots
+            .iter()
+            .filter_map(|root| {
+                let root_key = Uri::parse(&root.path).key();
+                let relative = key.strip_prefix(&root_key)?.strip_prefix('/')?;
+                // keep the spelling of the path, as the key may be case-folded
+                let relative = path.get(path.len() - relative.len()..)?;
+
+                Some((root, relative))
+            })
+            .max_by_key(|(root, _)| root.path.len())
+            .map_or((None, path.to_string()), |(root, relative)| {
+                (Some(root.name.clone()), relative.to_string())
+            })
+    }
+
+    /// The roots containing any of the files, in the order they were added.
+    pub(super) fn used_roots(&self, files: &[File]) -> Vec<WorkspaceRoot> {
+        self.roots
+            .iter()
+            .filter(|root| {
+                files
+                    .iter()
+                    .any(|file| file.root.as_ref() == Some(&root.name))
+            })
+            .cloned()
+            .collect()
+    }
+}
@@ -1,12 +1,93 @@
 use {
-    super::GraphGenerator,
-    crate::types::lsp::{DocumentSymbol, Position, Range, SymbolKind},
+    super::{FuncCallDirection, GraphGenerator, SymbolFilter},
+    crate::{
+        analysis::diff_graphs,
+        error::Error,
+        types::{
+            graph::{DropReason, RejectReason, RelationKind, Symbol},
+            lsp::{
+                CallHierarchyIncomingCall, CallHierarchyItem, CallHierarchyOutgoingCall,
+                DocumentSymbol, DocumentSymbolResponse, Location, Position, Range,
+                SymbolInformation, SymbolKind, SymbolTag, TypeHierarchyItem, Uri,
+            },
+        },
+    },
 };
 
+fn range(line: u32, start: u32, end: u32) -> Range {
+    Range {
+        start: Position {
+            line,
+            character: start,
+        },
+        end: Position {
+            line,
+            character: end,
+        },
+    }
+}
+
+/// A range from `start` to `end`, given as `(line, character)`.
+fn span(start: (u32, u32), end: (u32, u32)) -> Range {
+    Range {
+        start: Position {
+            line: start.0,
+            character: start.1,
+        },
+        end: Position {
+            line: end.0,
+            character: end.1,
+        },
+    }
+}
+
+/// A range over whole lines, like the body of a function.
+fn lines(start: u32, end: u32) -> Range {
+    span((start, 0), (end, 1))
+}
+
+fn symbol(name: &str, kind: SymbolKind, range: Range) -> DocumentSymbol {
+    DocumentSymbol {
+        name: name.to_string(),
+        detail: None,
+        kind,
+        tags: None,
+        range,
+        selection_range: range,
+        children: vec![],
+    }
+}
+
+fn call_item(path: &str, name: &str, kind: SymbolKind, range: Range) -> CallHierarchyItem {
+    CallHierarchyItem {
+        name: name.to_string(),
+        kind,
+        tags: None,
+        detail: None,
+        uri: Uri::parse(path),
+        range,
+        selection_range: range,
+        data: None,
+    }
+}
+
+fn type_item(path: &str, name: &str, kind: SymbolKind, range: Range) -> TypeHierarchyItem {
+    TypeHierarchyItem {
+        name: name.to_string(),
+        kind,
+        tags: None,
+        detail: None,
+        uri: Uri::parse(path),
+        range,
+        selection_range: range,
+        data: None,
+    }
+}
+
 #[test]
 #[allow(deprecated)]
 fn nested_function() {
-    let mut generator = GraphGenerator::new("");
+    let mut generator = GraphGenerator::new("", SymbolFilter::default());
     let parent_range = Range {
         start: Position {
             line: 1,
@@ -52,3 +133,1528 @@ fn nested_function() {
     let dot = generator.gen_graph();
     println!("{:#?}", dot);
 }
+
+#[test]
+fn inherit_relations() {
+    let mut generator = GraphGenerator::new("", SymbolFilter::default());
+    let base = range(1, 6, 10);
+    let derived = range(5, 6, 13);
+    let other = range(1, 10, 15);
+
+    generator.add_file(
+        "a.py".to_string(),
+        vec![
+            symbol("Base", SymbolKind::Class, base),
+            symbol("Derived", SymbolKind::Class, derived),
+        ],
+    );
+    generator.add_file(
+        "b.py".to_string(),
+        vec![symbol("Other", SymbolKind::Class, other)],
+    );
+
+    generator.add_type_hierarchy_supertypes(
+        "a.py".to_string(),
+        derived.start,
+        vec![type_item("a.py", "Base", SymbolKind::Class, base)],
+    );
+    generator.add_type_hierarchy_subtypes(
+        "a.py".to_string(),
+        base.start,
+        vec![
+            type_item("a.py", "Derived", SymbolKind::Class, derived),
+            type_item("b.py", "Other", SymbolKind::Class, other),
+        ],
+    );
+
+    let graph = generator.gen_graph();
+    let name = |id: &str| {
+        graph
+            .files
+            .iter()
+            .flat_map(|file| &file.symbols)
+            .find(|symbol| symbol.id == id)
+            .map(|symbol| symbol.name.clone())
+            .unwrap()
+    };
+    let mut relations = graph
+        .relations
+        .iter()
+        .map(|r| (name(&r.from_id), name(&r.to_id), r.kind))
+        .collect::<Vec<_>>();
+    relations.sort();
+    assert_eq!(
+        relations,
+        [
+            (
+                "Derived".to_string(),
+                "Base".to_string(),
+                RelationKind::Inherit
+            ),
+            (
+                "Other".to_string(),
+                "Base".to_string(),
+                RelationKind::Inherit
+            ),
+        ]
+    );
+}
+
+#[test]
+fn stable_output() {
+    let paths = ["c.py", "a.py", "b.py"];
+    let base = range(1, 6, 10);
+    let derived = range(5, 6, 13);
+
+    let graphs = [paths, [paths[2], paths[1], paths[0]]].map(|paths| {
+        let mut generator = GraphGenerator::new("", SymbolFilter::default());
+
+        for path in paths {
+            generator.add_type_hierarchy_supertypes(
+                path.to_string(),
+                derived.start,
+                vec![type_item("a.py", "Base", SymbolKind::Class, base)],
+            );
+        }
+        for path in paths {
+            generator.add_file(
+                path.to_string(),
+                vec![
+                    symbol("Base", SymbolKind::Class, base),
+                    symbol("Derived", SymbolKind::Class, derived),
+                ],
+            );
+        }
+
+        serde_json::to_string(&generator.gen_graph()).unwrap()
+    });
+
+    assert_eq!(graphs[0], graphs[1]);
+    assert!(
+        graphs[0].starts_with(r#"{"roots":[],"files":[{"id":1,"root":null,"relativePath":"a.py""#)
+    );
+}
+
+#[test]
+fn remove_and_update_file() {
+    let mut generator = GraphGenerator::new("", SymbolFilter::default());
+    let base = range(1, 6, 10);
+    let derived = range(1, 6, 13);
+
+    generator.add_file(
+        "a.py".to_string(),
+        vec![symbol("Base", SymbolKind::Class, base)],
+    );
+    generator.add_file(
+        "b.py".to_string(),
+        vec![symbol("Derived", SymbolKind::Class, derived)],
+    );
+    generator.add_type_hierarchy_supertypes(
+        "b.py".to_string(),
+        derived.start,
+        vec![type_item("a.py", "Base", SymbolKind::Class, base)],
+    );
+    assert_eq!(generator.gen_graph().relations.len(), 1);
+
+    assert!(generator.remove_file("b.py"));
+    assert!(!generator.remove_file("b.py"));
+    let graph = generator.gen_graph();
+    assert_eq!(graph.files.len(), 1);
+    assert!(graph.relations.is_empty());
+
+    generator.add_file(
+        "b.py".to_string(),
+        vec![symbol("Derived", SymbolKind::Class, derived)],
+    );
+    assert!(generator.update_file("a.py".to_string(), vec![]));
+    let graph = generator.gen_graph();
+    assert_eq!(graph.files.len(), 2);
+    assert!(graph.files[0].symbols.is_empty());
+}
+
+#[test]
+fn call_sites() {
+    let mut generator = GraphGenerator::new("", SymbolFilter::default());
+    let caller = span((1, 3), (5, 1));
+    let callee = range(7, 3, 9);
+    let sites = [range(2, 4, 10), range(3, 4, 10), range(4, 4, 10)];
+
+    generator.add_file(
+        "a.rs".to_string(),
+        vec![
+            symbol("caller", SymbolKind::Function, caller),
+            symbol("callee", SymbolKind::Function, callee),
+        ],
+    );
+    generator.add_incoming_calls(
+        "a.rs".to_string(),
+        callee.start,
+        vec![CallHierarchyIncomingCall {
+            from: call_item("a.rs", "caller", SymbolKind::Function, caller),
+            from_ranges: sites[..2].to_vec(),
+        }],
+    );
+    generator.add_outgoing_calls(
+        "a.rs".to_string(),
+        caller.start,
+        vec![CallHierarchyOutgoingCall {
+            to: call_item("a.rs", "callee", SymbolKind::Function, callee),
+            from_ranges: sites[1..].to_vec(),
+        }],
+    );
+
+    let graph = generator.gen_graph();
+    assert_eq!(graph.relations.len(), 1);
+    assert_eq!(graph.relations[0].count, 3);
+    assert_eq!(graph.relations[0].ranges, sites);
+}
+
+#[test]
+fn relations_of_different_kinds() {
+    let mut generator = GraphGenerator::new("", SymbolFilter::default());
+    let interface = range(1, 10, 11);
+    let class = range(3, 6, 7);
+
+    generator.add_file(
+        "i.ts".to_string(),
+        vec![symbol("I", SymbolKind::Interface, interface)],
+    );
+    generator.add_file(
+        "a.ts".to_string(),
+        vec![symbol("A", SymbolKind::Class, class)],
+    );
+    generator.add_interface_implementations(
+        "i.ts".to_string(),
+        interface.start,
+        vec![Location {
+            uri: Uri::parse("a.ts"),
+            range: class,
+        }],
+    );
+    generator.add_type_hierarchy_supertypes(
+        "a.ts".to_string(),
+        class.start,
+        vec![type_item("i.ts", "I", SymbolKind::Interface, interface)],
+    );
+
+    let graph = generator.gen_graph();
+    let kinds = graph
+        .relations
+        .iter()
+        .map(|relation| relation.kind)
+        .collect::<Vec<_>>();
+    assert_eq!(kinds, [RelationKind::Impl, RelationKind::Inherit]);
+
+    let graph = generator.gen_file_graph();
+    assert_eq!(graph.edges.len(), 1);
+    let counts = graph.edges[0].counts;
+    assert_eq!((counts.impls, counts.inherits), (1, 1));
+}
+
+#[test]
+fn func_graph_depth_and_direction() {
+    let mut generator = GraphGenerator::new("", SymbolFilter::default());
+    let funcs = ["a", "b", "c", "d"].map(|name| {
+        let line = name.as_bytes()[0] as u32;
+        (name, range(line, 3, 4))
+    });
+
+    generator.add_file(
+        "a.rs".to_string(),
+        funcs
+            .iter()
+            .map(|(name, range)| symbol(name, SymbolKind::Function, *range))
+            .collect(),
+    );
+    for pair in funcs.windows(2) {
+        let (caller, callee) = (pair[0], pair[1]);
+        generator.add_outgoing_calls(
+            "a.rs".to_string(),
+            caller.1.start,
+            vec![CallHierarchyOutgoingCall {
+                to: call_item("a.rs", callee.0, SymbolKind::Function, callee.1),
+                from_ranges: vec![],
+            }],
+        );
+    }
+
+    let names = |direction, max_depth| {
+        generator
+            .gen_func_graph("a.rs", funcs[1].1.start, max_depth, direction)
+            .files
+            .iter()
+            .flat_map(|file| file.symbols.iter().map(|s| s.name.clone()))
+            .collect::<Vec<_>>()
+    };
+
+    assert_eq!(names(FuncCallDirection::Outgoing, Some(1)), ["b", "c"]);
+    assert_eq!(names(FuncCallDirection::Outgoing, None), ["b", "c", "d"]);
+    assert_eq!(names(FuncCallDirection::Incoming, None), ["a", "b"]);
+    assert_eq!(
+        names(FuncCallDirection::Bidirection, Some(1)),
+        ["a", "b", "c"]
+    );
+
+    // the root may be reported at the start of the line rather than at the name
+    let mut root = funcs[1].1.start;
+    root.character = 0;
+    let graph = generator.gen_func_graph("a.rs", root, Some(1), FuncCallDirection::Outgoing);
+    assert_eq!(graph.files[0].symbols.len(), 2);
+}
+
+#[test]
+fn dead_code() {
+    let mut generator = GraphGenerator::new("Go", SymbolFilter::default());
+    let mut line = 0;
+    let funcs = ["main", "helper", "unused", "Exported", "(*server).serve"].map(|name| {
+        line += 1;
+        (name, range(line, 5, 9))
+    });
+
+    generator.add_file(
+        "main.go".to_string(),
+        funcs
+            .iter()
+            .map(|(name, range)| symbol(name, SymbolKind::Function, *range))
+            .collect(),
+    );
+    generator.add_outgoing_calls(
+        "main.go".to_string(),
+        funcs[0].1.start,
+        vec![CallHierarchyOutgoingCall {
+            to: call_item("main.go", "helper", SymbolKind::Function, funcs[1].1),
+            from_ranges: vec![],
+        }],
+    );
+
+    let unused = generator
+        .find_dead_code()
+        .into_iter()
+        .map(|symbol| symbol.name)
+        .collect::<Vec<_>>();
+    assert_eq!(unused, ["unused", "(*server).serve"]);
+}
+
+#[test]
+fn rust_entry_points() {
+    // names and details as reported by rust-analyzer, which leave out visibility
+    let function = |name: &str, detail: &str, line: u32| {
+        let mut function = symbol(
+            name,
+            SymbolKind::Function,
+            range(line, 7, 7 + name.len() as u32),
+        );
+        function.detail = Some(detail.to_string());
+        function
+    };
+    let block = |name: &str, line: u32, children: Vec<DocumentSymbol>| {
+        let mut block = symbol(name, SymbolKind::Object, range(line, 0, 4));
+        block.range.end.line = line + 9;
+        block.children = children;
+        block
+    };
+
+    let mut generator = GraphGenerator::new("Rust", SymbolFilter::default());
+    generator.add_file(
+        "/p/src/main.rs".to_string(),
+        vec![
+            function("main", "fn()", 1),
+            function("helper", "fn(u32) -> u32", 2),
+            function("testable_helper", "fn() -> bool", 3),
+            function("test_helper", "fn()", 4),
+            block(
+                "impl<T: Fn() -> u32> Display for Wrapper<T>",
+                10,
+                vec![function(
+                    "fmt",
+                    "fn(&self, &mut Formatter<'_>) -> fmt::Result",
+                    11,
+                )],
+            ),
+            block(
+                "impl<T> Wrapper<T>",
+                20,
+                vec![function("new", "fn(T) -> Self", 21)],
+            ),
+        ],
+    );
+
+    let mut parse = function("parse", "fn(&str) -> Result<Ast, Error>", 1);
+    parse.range.end.line = 5;
+    parse.children = vec![function("skip", "fn(&mut &str)", 2)];
+    generator.add_file(
+        "/p/src/lib.rs".to_string(),
+        vec![
+            parse,
+            block(
+                "impl Ast",
+                10,
+                vec![function("len", "fn(&self) -> usize", 11)],
+            ),
+        ],
+    );
+
+    let unused = generator
+        .find_dead_code()
+        .into_iter()
+        .map(|symbol| symbol.name)
+        .collect::<Vec<_>>();
+    // visibility is not reported, so unused public functions are listed too
+    assert_eq!(
+        unused,
+        ["parse", "skip", "len", "helper", "testable_helper", "new"]
+    );
+}
+
+#[test]
+fn entry_points_per_language() {
+    let unused = |lang: &str, path: &str, symbols: Vec<DocumentSymbol>| {
+        let mut generator = GraphGenerator::new(lang, SymbolFilter::default());
+        generator.add_file(path.to_string(), symbols);
+        generator
+            .find_dead_code()
+            .into_iter()
+            .map(|symbol| symbol.name)
+            .collect::<Vec<_>>()
+    };
+    let function = |name: &str, line| symbol(name, SymbolKind::Function, range(line, 4, 8));
+    let class = |name: &str, methods: Vec<DocumentSymbol>| DocumentSymbol {
+        range: lines(10, 30),
+        children: methods,
+        ..symbol(name, SymbolKind::Class, range(10, 6, 10))
+    };
+    let method = |name: &str, line| symbol(name, SymbolKind::Method, range(line, 8, 12));
+
+    assert_eq!(
+        unused(
+            "Python",
+            "/p/tests/test_app.py",
+            vec![
+                function("test_run", 1),
+                function("make_app", 2),
+                class("Widget", vec![method("__init__", 11), method("draw", 12)]),
+            ],
+        ),
+        ["make_app", "draw"]
+    );
+    assert_eq!(
+        unused("Python", "/p/app.py", vec![function("testable", 1)]),
+        ["testable"]
+    );
+    assert_eq!(
+        unused(
+            "Java",
+            "/p/src/main/java/App.java",
+            vec![class(
+                "App",
+                vec![
+                    method("main(String[])", 11),
+                    method("toString()", 12),
+                    method("helper()", 13)
+                ]
+            )],
+        ),
+        ["helper()"]
+    );
+    assert_eq!(
+        unused(
+            "Java",
+            "/p/src/main/java/AppTest.java",
+            vec![class("AppTest", vec![method("runs()", 11)])],
+        ),
+        Vec::<String>::new()
+    );
+    assert_eq!(
+        unused(
+            "TypeScript",
+            "/p/src/app.ts",
+            vec![class(
+                "App",
+                vec![method("constructor", 11), method("render", 12)]
+            )],
+        ),
+        ["render"]
+    );
+    assert!(unused(
+        "TypeScript",
+        "/p/src/app.test.ts",
+        vec![function("setup", 1)]
+    )
+    .is_empty());
+}
+
+#[test]
+fn file_graph() {
+    let mut generator = GraphGenerator::new("", SymbolFilter::default());
+    let base = range(1, 6, 10);
+    let method = range(2, 8, 12);
+    let derived = range(1, 6, 13);
+    let caller = range(2, 8, 14);
+
+    let mut class = symbol("Base", SymbolKind::Class, base);
+    class.children = vec![symbol("run", SymbolKind::Method, method)];
+    generator.add_file("a.py".to_string(), vec![class]);
+
+    let mut class = symbol("Derived", SymbolKind::Class, derived);
+    class.children = vec![symbol("start", SymbolKind::Method, caller)];
+    generator.add_file("b.py".to_string(), vec![class]);
+
+    generator.add_type_hierarchy_supertypes(
+        "b.py".to_string(),
+        derived.start,
+        vec![type_item("a.py", "Base", SymbolKind::Class, base)],
+    );
+    generator.add_outgoing_calls(
+        "b.py".to_string(),
+        caller.start,
+        vec![CallHierarchyOutgoingCall {
+            to: call_item("a.py", "run", SymbolKind::Method, method),
+            from_ranges: vec![range(3, 8, 11), range(4, 8, 11)],
+        }],
+    );
+
+    let graph = generator.gen_file_graph();
+    assert_eq!(
+        graph
+            .nodes
+            .iter()
+            .map(|node| node.symbol_count)
+            .collect::<Vec<_>>(),
+        [2, 2]
+    );
+    assert_eq!(graph.edges.len(), 1);
+
+    let edge = &graph.edges[0];
+    assert_eq!((edge.from, edge.to, edge.weight), (2, 1, 2));
+    assert_eq!((edge.counts.calls, edge.counts.inherits), (1, 1));
+}
+
+#[test]
+fn directory_graph() {
+    let mut generator = GraphGenerator::new("Rust", SymbolFilter::default());
+    let func = range(1, 3, 7);
+    let files = [
+        "/p/src/lib.rs",
+        "/p/src/net/mod.rs",
+        "/p/src/net/tcp.rs",
+        "/p/src/storage.rs",
+    ];
+
+    for path in files {
+        generator.add_file(
+            path.to_string(),
+            vec![symbol("f", SymbolKind::Function, func)],
+        );
+    }
+    for (from, to) in [(files[1], files[2]), (files[2], files[3])] {
+        generator.add_outgoing_calls(
+            from.to_string(),
+            func.start,
+            vec![CallHierarchyOutgoingCall {
+                to: call_item(to, "f", SymbolKind::Function, func),
+                from_ranges: vec![],
+            }],
+        );
+    }
+
+    let graph = generator.gen_directory_graph(2);
+    assert_eq!(
+        graph
+            .nodes
+            .iter()
+            .map(|node| (node.path.as_str(), node.file_count))
+            .collect::<Vec<_>>(),
+        [("src", 1), ("src/net", 2), ("src/storage", 1)]
+    );
+    assert_eq!(graph.edges.len(), 1);
+    assert_eq!((graph.edges[0].from, graph.edges[0].to), (2, 3));
+
+    let graph = generator.gen_directory_graph(1);
+    assert_eq!(graph.nodes.len(), 1);
+    assert!(graph.edges.is_empty());
+}
+
+#[test]
+fn save_and_load_state() {
+    let mut generator = GraphGenerator::new("", SymbolFilter::default());
+    let base = range(1, 6, 10);
+    let derived = range(1, 6, 13);
+    let caller = range(2, 8, 14);
+
+    generator.add_file(
+        "a.py".to_string(),
+        vec![symbol("Base", SymbolKind::Class, base)],
+    );
+    generator.record_file_content("a.py".to_string(), "class Base: ...");
+    generator.add_file(
+        "b.py".to_string(),
+        vec![
+            symbol("Derived", SymbolKind::Class, derived),
+            symbol("main", SymbolKind::Function, caller),
+        ],
+    );
+    generator.record_file_content("b.py".to_string(), "class Derived(Base): ...");
+    generator.add_type_hierarchy_supertypes(
+        "b.py".to_string(),
+        derived.start,
+        vec![type_item("a.py", "Base", SymbolKind::Class, base)],
+    );
+    generator.add_incoming_calls(
+        "b.py".to_string(),
+        derived.start,
+        vec![CallHierarchyIncomingCall {
+            from: call_item("b.py", "main", SymbolKind::Function, caller),
+            from_ranges: vec![range(3, 4, 11)],
+        }],
+    );
+
+    let state = generator.save_state();
+    let mut loaded = GraphGenerator::new("", SymbolFilter::default());
+    for _ in 0..2 {
+        loaded.add_file("c.py".to_string(), vec![]);
+    }
+    loaded.load_state(&state).unwrap();
+
+    assert_eq!(loaded.save_state(), state);
+    assert!(loaded
+        .gen_graph_with_diagnostics()
+        .1
+        .rejected_files
+        .is_empty());
+    assert_eq!(
+        serde_json::to_string(&loaded.gen_graph()).unwrap(),
+        serde_json::to_string(&generator.gen_graph()).unwrap()
+    );
+    assert!(loaded.is_file_unchanged("a.py", "class Base: ..."));
+
+    // recording the content before updating the symbols, as the editor does
+    loaded.record_file_content("a.py".to_string(), "class Base(object): ...");
+    loaded.update_file(
+        "a.py".to_string(),
+        vec![symbol("Base", SymbolKind::Class, base)],
+    );
+    let mut reloaded = GraphGenerator::new("", SymbolFilter::default());
+    reloaded.load_state(&loaded.save_state()).unwrap();
+    assert!(reloaded.is_file_unchanged("a.py", "class Base(object): ..."));
+    loaded.remove_file("a.py");
+    assert!(!loaded.is_file_unchanged("a.py", "class Base(object): ..."));
+    loaded.load_state(&state).unwrap();
+    assert!(!loaded.is_file_unchanged("b.py", "class Derived: ..."));
+    assert!(!loaded.is_file_unchanged("c.py", ""));
+
+    assert!(matches!(
+        loaded.load_state("{}"),
+        Err(Error::InvalidState(_))
+    ));
+    assert!(matches!(
+        loaded.load_state(&state.replacen(r#""version":1"#, r#""version":0"#, 1)),
+        Err(Error::UnsupportedStateVersion(0))
+    ));
+}
+
+#[test]
+fn stable_symbol_ids() {
+    let ids = |offset: u32| {
+        let mut generator = GraphGenerator::new("", SymbolFilter::default());
+        let class = range(offset + 1, 6, 12);
+        let methods = [range(offset + 2, 8, 11), range(offset + 3, 8, 11)];
+
+        let mut engine = symbol("Engine", SymbolKind::Class, class);
+        engine.children = methods
+            .iter()
+            .map(|range| symbol("run", SymbolKind::Method, *range))
+            .collect();
+        generator.add_file("a.ts".to_string(), vec![engine]);
+        generator.add_outgoing_calls(
+            "a.ts".to_string(),
+            methods[0].start,
+            vec![CallHierarchyOutgoingCall {
+                to: call_item("a.ts", "run", SymbolKind::Method, methods[1]),
+                from_ranges: vec![],
+            }],
+        );
+
+        let graph = generator.gen_graph();
+        let relation = &graph.relations[0];
+        let symbols = &graph.files[0].symbols;
+
+        (
+            symbols[0].id.clone(),
+            symbols[0]
+                .children
+                .iter()
+                .map(|s| s.id.clone())
+                .collect::<Vec<_>>(),
+            (relation.from_id.clone(), relation.to_id.clone()),
+        )
+    };
+
+    let (class, methods, relation) = ids(0);
+    assert_eq!(class, "a.ts#5:Engine");
+    assert_eq!(methods, ["a.ts#5:Engine/6:run", "a.ts#5:Engine/6:run~1"]);
+    assert_eq!(relation, (methods[0].clone(), methods[1].clone()));
+
+    assert_eq!(ids(10), (class, methods, relation));
+
+    // paths may contain `#`, and names the separators of ids
+    let mut generator = GraphGenerator::new("", SymbolFilter::default());
+    let mut vector = symbol("Vector", SymbolKind::Class, lines(1, 9));
+    vector.children = vec![
+        symbol("operator/", SymbolKind::Operator, lines(2, 3)),
+        symbol("operator/", SymbolKind::Operator, lines(4, 5)),
+        symbol("~Vector", SymbolKind::Constructor, lines(6, 7)),
+    ];
+    let mut class = symbol("Vector#1", SymbolKind::Class, lines(10, 14));
+    class.children = vec![symbol("run", SymbolKind::Method, lines(11, 12))];
+    generator.add_file("ws/C#/a.cs".to_string(), vec![vector, class]);
+
+    let graph = generator.gen_graph();
+    let symbols = &graph.files[0].symbols;
+    assert_eq!(symbols[0].id, "ws/C#/a.cs#5:Vector");
+    assert_eq!(
+        symbols[0]
+            .children
+            .iter()
+            .map(|s| s.id.as_str())
+            .collect::<Vec<_>>(),
+        [
+            "ws/C#/a.cs#5:Vector/25:operator%2F",
+            "ws/C#/a.cs#5:Vector/25:operator%2F~1",
+            "ws/C#/a.cs#5:Vector/9:%7EVector",
+        ]
+    );
+    assert_eq!(symbols[1].id, "ws/C#/a.cs#5:Vector%231");
+    assert_eq!(symbols[1].children[0].id, "ws/C#/a.cs#5:Vector%231/6:run");
+}
+
+#[test]
+fn qualified_names() {
+    let names = |lang: &str, path: &str, container: &str, name: &str| {
+        let mut generator = GraphGenerator::new(lang, SymbolFilter::default());
+        let mut parent = symbol(container, SymbolKind::Class, range(1, 0, 10));
+        parent.children = vec![symbol(name, SymbolKind::Method, range(2, 4, 8))];
+        generator.add_file(path.to_string(), vec![parent]);
+
+        let graph = generator.gen_graph();
+        let parent = &graph.files[0].symbols[0];
+        (
+            parent.qualified_name.clone(),
+            parent.children[0].qualified_name.clone(),
+        )
+    };
+
+    assert_eq!(
+        names(
+            "Rust",
+            "/p/src/storage.rs",
+            "impl<T> Flush for Engine<T>",
+            "flush"
+        ),
+        (
+            "storage::Engine<T>".to_string(),
+            "storage::Engine<T>::flush".to_string()
+        )
+    );
+    assert_eq!(
+        names("Rust", "/p/src/lib.rs", "impl Engine", "new").1,
+        "Engine::new"
+    );
+    assert_eq!(
+        names("Go", "/p/pkg/server.go", "Server", "Serve").1,
+        "pkg.Server.Serve"
+    );
+    assert_eq!(
+        names("TypeScript", "/p/src/a.ts", "Engine", "run").1,
+        "Engine.run"
+    );
+}
+
+#[test]
+fn details_and_tags() {
+    let mut generator = GraphGenerator::new("", SymbolFilter::default());
+    let outer = lines(1, 10);
+    let nested = span((3, 4), (5, 5));
+    let old = range(20, 3, 6);
+
+    let mut deprecated = symbol("old", SymbolKind::Function, old);
+    deprecated.detail = Some("fn old()".to_string());
+    deprecated.tags = Some(vec![SymbolTag::Deprecated]);
+    generator.add_file(
+        "a.rs".to_string(),
+        vec![symbol("outer", SymbolKind::Function, outer), deprecated],
+    );
+
+    let mut caller = call_item("a.rs", "nested", SymbolKind::Function, nested);
+    caller.detail = Some("fn nested()".to_string());
+    generator.add_incoming_calls(
+        "a.rs".to_string(),
+        old.start,
+        vec![CallHierarchyIncomingCall {
+            from: caller,
+            from_ranges: vec![],
+        }],
+    );
+
+    let graph = generator.gen_graph();
+    let symbols = &graph.files[0].symbols;
+    assert_eq!(symbols[1].detail.as_deref(), Some("fn old()"));
+    assert_eq!(symbols[1].tags, [SymbolTag::Deprecated]);
+    assert_eq!(
+        symbols[0].children[0].detail.as_deref(),
+        Some("fn nested()")
+    );
+    assert!(symbols[0].children[0].tags.is_empty());
+}
+
+#[test]
+fn full_ranges() {
+    let mut generator = GraphGenerator::new("", SymbolFilter::default());
+    let outer = lines(1, 10);
+    let nested = span((3, 4), (5, 5));
+    let callee = range(20, 3, 6);
+
+    let mut func = symbol("outer", SymbolKind::Function, outer);
+    func.selection_range = range(1, 3, 8);
+    generator.add_file(
+        "a.rs".to_string(),
+        vec![func, symbol("callee", SymbolKind::Function, callee)],
+    );
+
+    let mut caller = call_item("a.rs", "nested", SymbolKind::Function, nested);
+    caller.selection_range = range(3, 7, 13);
+    generator.add_incoming_calls(
+        "a.rs".to_string(),
+        callee.start,
+        vec![CallHierarchyIncomingCall {
+            from: caller,
+            from_ranges: vec![],
+        }],
+    );
+
+    let graph = generator.gen_graph();
+    let file = &graph.files[0];
+    let outer_symbol = &file.symbols[0];
+    assert_eq!(outer_symbol.range, outer);
+    assert_eq!(outer_symbol.selection_range, range(1, 3, 8));
+
+    let nested_symbol = &outer_symbol.children[0];
+    assert_eq!(nested_symbol.range, nested);
+    assert_eq!(nested_symbol.selection_range, range(3, 7, 13));
+    assert_eq!(
+        (
+            graph.relations[0].from.line,
+            graph.relations[0].from.character
+        ),
+        (3, 7)
+    );
+
+    let at = |line| {
+        file.symbol_at(Position { line, character: 4 })
+            .map(|symbol| symbol.name.as_str())
+    };
+    assert_eq!(at(4), Some("nested"));
+    assert_eq!(at(8), Some("outer"));
+    assert_eq!(at(15), None);
+}
+
+#[test]
+fn flat_symbols() {
+    let info = |name: &str, kind, range, container: Option<&str>| SymbolInformation {
+        name: name.to_string(),
+        kind,
+        tags: None,
+        deprecated: None,
+        location: Location {
+            uri: Uri::parse("a.rs"),
+            range,
+        },
+        container_name: container.map(str::to_string),
+    };
+
+    let mut generator = GraphGenerator::new("", SymbolFilter::none());
+    let mut old = info("old", SymbolKind::Function, lines(12, 14), None);
+    old.deprecated = Some(true);
+    generator.add_flat_file(
+        "a.rs".to_string(),
+        vec![
+            old,
+            info("helper", SymbolKind::Function, lines(3, 4), Some("run")),
+            info("Engine", SymbolKind::Struct, lines(0, 10), None),
+            info("run", SymbolKind::Method, lines(2, 5), Some("Engine")),
+            info("stop", SymbolKind::Method, lines(6, 8), Some("Engine")),
+        ],
+    );
+
+    let graph = generator.gen_graph();
+    let symbols = &graph.files[0].symbols;
+    assert_eq!(symbols.len(), 2);
+    assert_eq!(symbols[0].qualified_name, "Engine");
+    assert_eq!(symbols[0].range, lines(0, 10));
+
+    let methods = &symbols[0].children;
+    assert_eq!(methods.len(), 2);
+    assert_eq!(methods[0].qualified_name, "Engine.run");
+    assert_eq!(methods[0].children[0].qualified_name, "Engine.run.helper");
+    assert_eq!(methods[1].qualified_name, "Engine.stop");
+
+    assert_eq!(symbols[1].name, "old");
+    assert_eq!(symbols[1].tags, [SymbolTag::Deprecated]);
+
+    // servers reporting only the range of the name
+    generator.add_flat_file(
+        "b.rs".to_string(),
+        vec![
+            info("Engine", SymbolKind::Struct, range(0, 6, 12), None),
+            info("run", SymbolKind::Method, range(2, 4, 7), Some("Engine")),
+            info("main", SymbolKind::Function, range(5, 3, 7), None),
+        ],
+    );
+
+    let graph = generator.gen_graph();
+    let symbols = &graph.files[1].symbols;
+    let names = symbols
+        .iter()
+        .map(|symbol| symbol.name.as_str())
+        .collect::<Vec<_>>();
+    assert_eq!(names, ["Engine", "main"]);
+    assert_eq!(symbols[0].children[0].qualified_name, "Engine.run");
+    assert_eq!(symbols[0].selection_range, range(0, 6, 12));
+    assert_eq!(symbols[0].range.end, range(2, 4, 7).end);
+
+    // responses are read in the form of their first symbol, whose errors are reported
+    let response = serde_json::from_value::<DocumentSymbolResponse>;
+    let nested = serde_json::to_value([symbol("main", SymbolKind::Function, lines(0, 2))]).unwrap();
+    let flat =
+        serde_json::to_value([info("main", SymbolKind::Function, lines(0, 2), None)]).unwrap();
+    assert!(matches!(
+        response(nested.clone()),
+        Ok(DocumentSymbolResponse::Nested(_))
+    ));
+    assert!(matches!(
+        response(flat.clone()),
+        Ok(DocumentSymbolResponse::Flat(_))
+    ));
+    assert_eq!(
+        response(serde_json::json!([])).unwrap(),
+        DocumentSymbolResponse::Nested(vec![])
+    );
+
+    for (mut value, form) in [
+        (nested, "invalid document symbols"),
+        (flat, "invalid symbol information"),
+    ] {
+        value[0]["kind"] = "x".into();
+        let error = response(value).unwrap_err().to_string();
+        assert!(
+            error.starts_with(form) && error.contains(r#""x""#),
+            "{error}"
+        );
+    }
+}
+
+#[test]
+fn uri_spellings() {
+    assert_eq!(
+        Uri::parse("file:///c%3A/src/./lib/../main.rs").canonical(),
+        "/C:/src/main.rs"
+    );
+    assert_eq!(Uri::parse(r"c:\src\main.rs").canonical(), "/C:/src/main.rs");
+    assert_eq!(
+        Uri::parse("file://server/share/a%20b.rs").canonical(),
+        "//server/share/a b.rs"
+    );
+    assert_eq!(Uri::parse("/home/me/a.rs").canonical(), "/home/me/a.rs");
+    assert_eq!(
+        Uri::parse("vscode-notebook-cell:/nb.ipynb#W1").canonical(),
+        "vscode-notebook-cell:/nb.ipynb#W1"
+    );
+    assert_eq!(
+        Uri::parse("C:/Src/A.rs").key(),
+        Uri::parse("/c:/src/a.rs").key()
+    );
+    assert_ne!(Uri::parse("/src/A.rs").key(), Uri::parse("/src/a.rs").key());
+
+    let mut generator = GraphGenerator::new("", SymbolFilter::default());
+    let callee = range(0, 3, 9);
+    let caller = range(5, 3, 9);
+
+    generator.add_file(
+        "file:///c%3A/Src/a.rs".to_string(),
+        vec![symbol("callee", SymbolKind::Function, callee)],
+    );
+    generator.add_file(
+        "untitled:Untitled-1".to_string(),
+        vec![symbol("caller", SymbolKind::Function, caller)],
+    );
+    generator.add_incoming_calls(
+        r"C:\Src\a.rs".to_string(),
+        callee.start,
+        vec![CallHierarchyIncomingCall {
+            from: call_item(
+                "untitled:Untitled-1",
+                "caller",
+                SymbolKind::Function,
+                caller,
+            ),
+            from_ranges: vec![],
+        }],
+    );
+    generator.add_outgoing_calls(
+        "untitled:Untitled-1".to_string(),
+        caller.start,
+        vec![CallHierarchyOutgoingCall {
+            to: call_item("/c:/src/A.rs", "callee", SymbolKind::Function, callee),
+            from_ranges: vec![],
+        }],
+    );
+
+    let graph = generator.gen_graph();
+    let paths = graph
+        .files
+        .iter()
+        .map(|file| file.path.as_str())
+        .collect::<Vec<_>>();
+    assert_eq!(paths, ["/C:/Src/a.rs", "untitled:Untitled-1"]);
+    assert_eq!(graph.relations.len(), 1);
+    assert_eq!(graph.relations[0].count, 0);
+
+    assert!(generator.remove_file("file:///C:/src/a.rs"));
+}
+
+#[test]
+fn workspace_roots() {
+    let mut generator = GraphGenerator::new("", SymbolFilter::default());
+    generator.add_root("frontend".to_string(), "file:///ws/frontend".to_string());
+    generator.add_root("backend".to_string(), "/ws/backend".to_string());
+    generator.add_root("api".to_string(), "/ws/backend/api".to_string());
+    generator.add_root("docs".to_string(), "/ws/docs".to_string());
+
+    for path in [
+        "/ws/backend/src/main.rs",
+        "/ws/backend/api/lib.rs",
+        "/ws/frontend/app.ts",
+        "/ws/backend-old/main.rs",
+    ] {
+        generator.add_file(
+            path.to_string(),
+            vec![symbol("main", SymbolKind::Function, range(0, 3, 7))],
+        );
+    }
+
+    let graph = generator.gen_graph();
+    let roots = graph
+        .roots
+        .iter()
+        .map(|root| (root.name.as_str(), root.path.as_str()))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        roots,
+        [
+            ("frontend", "/ws/frontend"),
+            ("backend", "/ws/backend"),
+            ("api", "/ws/backend/api")
+        ]
+    );
+
+    let files = graph
+        .files
+        .iter()
+        .map(|file| (file.root.as_deref(), file.relative_path.as_str()))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        files,
+        [
+            (None, "/ws/backend-old/main.rs"),
+            (Some("api"), "lib.rs"),
+            (Some("backend"), "src/main.rs"),
+            (Some("frontend"), "app.ts"),
+        ]
+    );
+
+    // absolute locations stay internal, except for files outside of every root
+    let json = serde_json::to_string(&graph).unwrap();
+    assert!(!json.contains("/ws/backend/") && !json.contains("/ws/frontend"));
+    assert!(json.contains(r#"{"name":"api"}"#));
+    assert_eq!(
+        generator
+            .gen_file_graph()
+            .nodes
+            .iter()
+            .map(|node| node.path.as_str())
+            .collect::<Vec<_>>(),
+        [
+            "/ws/backend-old/main.rs",
+            "api/lib.rs",
+            "backend/src/main.rs",
+            "frontend/app.ts"
+        ]
+    );
+}
+
+#[test]
+fn diagnostics() {
+    let mut generator = GraphGenerator::new("Go", SymbolFilter::default());
+    let main = range(2, 5, 9);
+    let limit = range(0, 6, 11);
+    let top_level = range(9, 0, 4);
+
+    assert!(generator.add_file(
+        "main.go".to_string(),
+        vec![
+            symbol("limit", SymbolKind::Constant, limit),
+            symbol("main", SymbolKind::Function, main),
+        ],
+    ));
+    assert!(!generator.add_file("main.go".to_string(), vec![]));
+    assert!(!generator.add_file("main_test.go".to_string(), vec![]));
+
+    generator.add_incoming_calls(
+        "main.go".to_string(),
+        main.start,
+        vec![
+            CallHierarchyIncomingCall {
+                from: call_item("other.go", "run", SymbolKind::Function, range(4, 5, 8)),
+                from_ranges: vec![],
+            },
+            CallHierarchyIncomingCall {
+                from: call_item("main.go", "init", SymbolKind::Variable, top_level),
+                from_ranges: vec![],
+            },
+        ],
+    );
+    generator.add_outgoing_calls(
+        "main.go".to_string(),
+        main.start,
+        vec![CallHierarchyOutgoingCall {
+            to: call_item("main.go", "limit", SymbolKind::Constant, limit),
+            from_ranges: vec![],
+        }],
+    );
+
+    let (graph, diagnostics) = generator.gen_graph_with_diagnostics();
+    assert!(graph.relations.is_empty());
+
+    let rejected = diagnostics
+        .rejected_files
+        .iter()
+        .map(|file| (file.path.as_str(), file.reason))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        rejected,
+        [
+            ("main.go", RejectReason::Duplicate),
+            ("main_test.go", RejectReason::FilteredOut)
+        ]
+    );
+
+    assert_eq!(diagnostics.unresolved_calls.len(), 1);
+    let unresolved = &diagnostics.unresolved_calls[0];
+    assert_eq!(unresolved.name, "run");
+    assert_eq!(unresolved.from.path, "other.go");
+    assert_eq!(unresolved.to.position, main.start);
+
+    let dropped = diagnostics
+        .dropped_relations
+        .iter()
+        .map(|relation| {
+            (
+                relation.from.position,
+                relation.to.position,
+                relation.reason,
+            )
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(
+        dropped,
+        [
+            (main.start, limit.start, DropReason::UnknownTarget),
+            (top_level.start, main.start, DropReason::UnknownSource),
+        ]
+    );
+}
+
+#[test]
+fn fuzzy_matching() {
+    let func = |name: &str, body: Range, selection: Range| DocumentSymbol {
+        selection_range: selection,
+        ..symbol(name, SymbolKind::Function, body)
+    };
+    let at = |line, character| Position { line, character };
+
+    let mut generator = GraphGenerator::new("", SymbolFilter::default());
+    generator.add_file(
+        "a.c".to_string(),
+        vec![
+            func("caller", lines(0, 3), range(0, 5, 11)),
+            func("callee", lines(5, 8), range(5, 5, 11)),
+            func("expand", lines(10, 20), range(10, 4, 10)),
+        ],
+    );
+
+    // keyed on the `fn` keyword, called from one column off the name
+    generator.add_incoming_calls(
+        "a.c".to_string(),
+        at(5, 0),
+        vec![CallHierarchyIncomingCall {
+            from: call_item("a.c", "caller", SymbolKind::Function, range(0, 4, 10)),
+            from_ranges: vec![],
+        }],
+    );
+    // calls into the expansion of a macro, and to an unknown symbol at the same position
+    generator.add_outgoing_calls(
+        "a.c".to_string(),
+        at(0, 5),
+        vec![
+            CallHierarchyOutgoingCall {
+                to: call_item("a.c", "expand", SymbolKind::Function, range(14, 2, 8)),
+                from_ranges: vec![],
+            },
+            CallHierarchyOutgoingCall {
+                to: call_item("a.c", "other", SymbolKind::Function, range(14, 2, 8)),
+                from_ranges: vec![],
+            },
+        ],
+    );
+
+    let (graph, diagnostics) = generator.gen_graph_with_diagnostics();
+    let relations = graph
+        .relations
+        .iter()
+        .map(|relation| (relation.from_id.as_str(), relation.to_id.as_str()))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        relations,
+        [
+            ("a.c#12:caller", "a.c#12:callee"),
+            ("a.c#12:caller", "a.c#12:expand")
+        ]
+    );
+
+    assert_eq!(diagnostics.dropped_relations.len(), 1);
+    assert_eq!(
+        diagnostics.dropped_relations[0].reason,
+        DropReason::UnknownTarget
+    );
+
+    // `const a = 1; function b() {}`, with calls keyed on the constant, which is filtered out
+    let mut generator = GraphGenerator::new("", SymbolFilter::default());
+    generator.add_file(
+        "b.ts".to_string(),
+        vec![
+            symbol("a", SymbolKind::Constant, range(0, 6, 7)),
+            func("b", range(0, 13, 28), range(0, 22, 23)),
+            func("c", lines(2, 4), range(2, 9, 10)),
+        ],
+    );
+    generator.add_incoming_calls(
+        "b.ts".to_string(),
+        at(0, 6),
+        vec![CallHierarchyIncomingCall {
+            from: call_item("b.ts", "c", SymbolKind::Function, range(2, 9, 10)),
+            from_ranges: vec![],
+        }],
+    );
+
+    let (graph, diagnostics) = generator.gen_graph_with_diagnostics();
+    assert!(graph.relations.is_empty());
+    assert_eq!(diagnostics.dropped_relations.len(), 1);
+}
+
+#[test]
+fn external_calls() {
+    let mut generator = GraphGenerator::new("Rust", SymbolFilter::default());
+    generator.add_root("app".to_string(), "/ws/app".to_string());
+    generator.set_keep_external_calls(true);
+
+    let main = range(0, 3, 7);
+    generator.add_file(
+        "/ws/app/src/main.rs".to_string(),
+        vec![symbol("main", SymbolKind::Function, main)],
+    );
+
+    let registry = "/home/me/.cargo/registry/src/index.crates.io-6f17d22bba15001f";
+    let external = |path: String, name: &str, line| CallHierarchyOutgoingCall {
+        to: call_item(&path, name, SymbolKind::Function, range(line, 7, 12)),
+        from_ranges: vec![],
+    };
+    generator.add_outgoing_calls(
+        "/ws/app/src/main.rs".to_string(),
+        main.start,
+        vec![
+            external(
+                format!("{registry}/serde_json-1.0.1/src/de.rs"),
+                "from_str",
+                10,
+            ),
+            external(
+                format!("{registry}/serde_json-1.0.1/src/ser.rs"),
+                "to_string",
+                20,
+            ),
+            external(
+                format!("{registry}/serde_json-1.0.1/src/ser.rs"),
+                "to_vec",
+                30,
+            ),
+            external(format!("{registry}/anyhow-1.0.0/src/lib.rs"), "anyhow", 5),
+            // not external, as it is in the workspace
+            external("/ws/app/tests/it.rs".to_string(), "helper", 1),
+        ],
+    );
+
+    let (graph, diagnostics) = generator.gen_graph_with_diagnostics();
+    let packages = graph
+        .externals
+        .iter()
+        .map(|package| {
+            let files = package
+                .files
+                .iter()
+                .map(|file| (file.id, file.relative_path.as_str(), file.symbols.len()))
+                .collect::<Vec<_>>();
+            (package.name.as_str(), files)
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(
+        packages,
+        [
+            ("anyhow-1.0.0", vec![(2, "src/lib.rs", 1)]),
+            (
+                "serde_json-1.0.1",
+                vec![(3, "src/de.rs", 1), (4, "src/ser.rs", 2)]
+            ),
+        ]
+    );
+    assert_eq!(
+        graph.externals[1].path,
+        format!("{registry}/serde_json-1.0.1")
+    );
+    let package = serde_json::to_value(&graph.externals[1]).unwrap();
+    assert!(package.get("path").is_none());
+
+    let to_ids = graph
+        .relations
+        .iter()
+        .map(|relation| (relation.to.file_id, relation.to_id.as_str()))
+        .collect::<Vec<_>>();
+    assert_eq!(to_ids.len(), 4);
+    assert!(to_ids.contains(&(4, "serde_json-1.0.1/src/ser.rs#12:to_vec")));
+
+    assert_eq!(diagnostics.unresolved_calls.len(), 1);
+    assert!(generator.gen_file_graph().edges.is_empty());
+
+    // calls from a file that was never added, and from a symbol filtered out
+    let mut generator = GraphGenerator::new("Rust", SymbolFilter::default());
+    generator.add_root("app".to_string(), "/ws/app".to_string());
+    generator.set_keep_external_calls(true);
+
+    let handler = range(2, 4, 11);
+    generator.add_file(
+        "/ws/app/src/main.rs".to_string(),
+        vec![symbol("HANDLER", SymbolKind::Variable, handler)],
+    );
+    for (path, position) in [
+        ("/ws/app/src/b.rs", range(1, 3, 4).start),
+        ("/ws/app/src/main.rs", handler.start),
+    ] {
+        generator.add_outgoing_calls(
+            path.to_string(),
+            position,
+            vec![external(
+                format!("{registry}/anyhow-1.0.0/src/lib.rs"),
+                "anyhow",
+                5,
+            )],
+        );
+    }
+
+    let (graph, diagnostics) = generator.gen_graph_with_diagnostics();
+    assert!(graph.relations.is_empty());
+    assert!(graph.externals.is_empty());
+    let reasons = diagnostics
+        .dropped_relations
+        .iter()
+        .map(|relation| (relation.from.path.as_str(), relation.reason))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        reasons,
+        [
+            ("/ws/app/src/b.rs", DropReason::UnknownSource),
+            ("/ws/app/src/main.rs", DropReason::UnknownSource)
+        ]
+    );
+}
+
+#[test]
+fn nested_symbol_insertion() {
+    let with_children = |mut symbol: DocumentSymbol, children| {
+        symbol.children = children;
+        symbol
+    };
+
+    let mut generator = GraphGenerator::new("TypeScript", SymbolFilter::default());
+    let handle = lines(70, 72);
+    generator.add_file(
+        "a.ts".to_string(),
+        vec![
+            with_children(
+                symbol("Widget", SymbolKind::Class, lines(0, 30)),
+                vec![with_children(
+                    symbol("render", SymbolKind::Method, lines(2, 20)),
+                    vec![
+                        symbol("first", SymbolKind::Function, lines(6, 7)),
+                        symbol("second", SymbolKind::Function, lines(9, 10)),
+                        symbol("after", SymbolKind::Function, lines(14, 15)),
+                    ],
+                )],
+            ),
+            symbol("build", SymbolKind::Function, lines(40, 60)),
+            symbol("handle", SymbolKind::Function, handle),
+        ],
+    );
+
+    generator.add_incoming_calls(
+        "a.ts".to_string(),
+        handle.start,
+        vec![
+            CallHierarchyIncomingCall {
+                from: call_item("a.ts", "onClick", SymbolKind::Function, lines(5, 12)),
+                from_ranges: vec![],
+            },
+            CallHierarchyIncomingCall {
+                from: call_item("a.ts", "Local", SymbolKind::Class, lines(42, 50)),
+                from_ranges: vec![],
+            },
+        ],
+    );
+
+    let graph = generator.gen_graph();
+    assert_eq!(graph.relations.len(), 2);
+
+    let symbols = &graph.files[0].symbols;
+    let render = &symbols[0].children[0];
+    let names = |symbols: &[Symbol]| {
+        symbols
+            .iter()
+            .map(|symbol| symbol.name.clone())
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(names(&render.children), ["onClick", "after"]);
+    assert_eq!(names(&render.children[0].children), ["first", "second"]);
+    assert_eq!(render.children[0].qualified_name, "Widget.render.onClick");
+    assert_eq!(names(&symbols[1].children), ["Local"]);
+}
+
+#[test]
+fn symbol_filter_rules() {
+    let filter: SymbolFilter = serde_json::from_value(serde_json::json!({
+        "includeKinds": ["constant"],
+        "excludeKinds": ["Struct"],
+        "excludeNames": ["test_*"],
+        "includePaths": ["src/**"],
+        "excludePaths": ["**/generated/**"],
+    }))
+    .unwrap();
+    assert!(filter.language_defaults);
+
+    let mut generator = GraphGenerator::new("", filter);
+    generator.add_root("ws".to_string(), "/ws".to_string());
+
+    assert!(!generator.should_filter_out_file("/ws/src/main.rs"));
+    assert!(generator.should_filter_out_file("/ws/src/generated/api.rs"));
+    assert!(generator.should_filter_out_file("/ws/generated/api.rs"));
+    assert!(generator.should_filter_out_file("/ws/tests/it.rs"));
+
+    let mut module = symbol("tests", SymbolKind::Module, range(0, 4, 9));
+    module.range.end.line = 20;
+    module.children = vec![
+        symbol("test_run", SymbolKind::Function, range(1, 7, 15)),
+        symbol("run", SymbolKind::Function, range(5, 7, 10)),
+        symbol("LIMIT", SymbolKind::Constant, range(9, 6, 11)),
+        symbol("count", SymbolKind::Variable, range(10, 8, 13)),
+        symbol("Config", SymbolKind::Struct, range(12, 11, 17)),
+    ];
+    assert!(generator.add_file("/ws/src/main.rs".to_string(), vec![module]));
+    assert!(!generator.add_file(
+        "/ws/src/generated/api.rs".to_string(),
+        vec![symbol("api", SymbolKind::Function, range(0, 3, 6))],
+    ));
+
+    let (graph, diagnostics) = generator.gen_graph_with_diagnostics();
+    assert_eq!(graph.files.len(), 1);
+    let names = graph.files[0].symbols[0]
+        .children
+        .iter()
+        .map(|symbol| symbol.name.as_str())
+        .collect::<Vec<_>>();
+    assert_eq!(names, ["run", "LIMIT"]);
+    assert_eq!(
+        diagnostics.rejected_files[0].reason,
+        RejectReason::FilteredOut
+    );
+
+    let error = serde_json::from_value::<SymbolFilter>(serde_json::json!({
+        "excludeKinds": ["Widget"],
+    }))
+    .unwrap_err();
+    assert!(error.to_string().contains("unknown symbol kind `Widget`"));
+}
+
+#[test]
+fn ids_across_checkouts() {
+    let graph = |checkout: &str, callee: &str| {
+        let mut generator = GraphGenerator::new("", SymbolFilter::default());
+        generator.add_root("crate".to_string(), checkout.to_string());
+
+        let path = format!("{checkout}/src/lib.rs");
+        let funcs = [range(1, 3, 7), range(5, 3, 8)];
+        generator.add_file(
+            path.clone(),
+            vec![
+                symbol("main", SymbolKind::Function, funcs[0]),
+                symbol(callee, SymbolKind::Function, funcs[1]),
+            ],
+        );
+        generator.add_outgoing_calls(
+            path.clone(),
+            funcs[0].start,
+            vec![CallHierarchyOutgoingCall {
+                to: call_item(&path, callee, SymbolKind::Function, funcs[1]),
+                from_ranges: vec![],
+            }],
+        );
+        generator.gen_graph()
+    };
+
+    let old = graph("/work/main", "parse");
+    assert_eq!(old.files[0].symbols[0].id, "crate/src/lib.rs#12:main");
+
+    let diff = diff_graphs(&old, &graph("/work/feature", "parse"));
+    assert!(diff.added_files.is_empty() && diff.removed_files.is_empty());
+    assert!(diff.added_symbols.is_empty() && diff.added_relations.is_empty());
+
+    let diff = diff_graphs(&old, &graph("/work/feature", "lex"));
+    assert_eq!(diff.added_symbols, ["crate/src/lib.rs#12:lex"]);
+    assert_eq!(diff.removed_symbols, ["crate/src/lib.rs#12:parse"]);
+}
+
+#[test]
+fn pathological_path_patterns() {
+    let filter: SymbolFilter = serde_json::from_value(serde_json::json!({
+        "excludePaths": ["**/*a*a*a*a*a*a*a*a*a*a*a*a*b", "src/?ib.rs", "**/gen/**/*.rs"],
+    }))
+    .unwrap();
+    let generator = GraphGenerator::new("", filter);
+
+    let start = std::time::Instant::now();
+    let path = format!("/ws/{}.rs", "a".repeat(200));
+    assert!(!generator.should_filter_out_file(&path));
+    assert!(start.elapsed() < std::time::Duration::from_secs(1));
+
+    assert!(generator.should_filter_out_file("/ws/aaaaaaaaaaaaab"));
+    assert!(generator.should_filter_out_file("src/lib.rs"));
+    assert!(!generator.should_filter_out_file("src/li/b.rs"));
+    assert!(generator.should_filter_out_file("/ws/gen/a.rs"));
+    assert!(generator.should_filter_out_file("/ws/gen/x/y/a.rs"));
+    assert!(!generator.should_filter_out_file("/ws/gen/a.ts"));
+}
@@ -1,8 +1,14 @@
 use {
-    super::GraphGenerator,
-    crate::types::lsp::{
-        CallHierarchyIncomingCall, CallHierarchyOutgoingCall, DocumentSymbol, Location, Position,
+    super::{FuncCallDirection, GraphGenerator},
+    crate::{
+        analysis::{compute_metrics, diff_graphs, find_cycles},
+        error::{self, Error},
+        types::lsp::{
+            CallHierarchyIncomingCall, CallHierarchyOutgoingCall, DocumentSymbolResponse, Location,
+            Position, TypeHierarchyItem,
+        },
     },
+    serde::{de::DeserializeOwned, Serialize},
     std::cell::RefCell,
     wasm_bindgen::prelude::*,
 };
@@ -35,40 +41,86 @@ pub struct GraphGeneratorWasm {
 #[wasm_bindgen(js_class = GraphGenerator)]
 impl GraphGeneratorWasm {
     #[wasm_bindgen(constructor)]
-    pub fn new(lang: String, filter: bool) -> Self {
-        Self {
+    pub fn new(lang: String, filter: JsValue) -> Result<GraphGeneratorWasm, JsError> {
+        let filter = from_js("filter", filter)?;
+        Ok(Self {
             inner: RefCell::new(GraphGenerator::new(&lang, filter)),
-        }
+        })
+    }
+
+    pub fn add_root(&self, name: String, path: String) {
+        self.inner.borrow_mut().add_root(name, path);
+    }
+
+    pub fn set_keep_external_calls(&self, keep: bool) {
+        self.inner.borrow_mut().set_keep_external_calls(keep);
     }
 
     pub fn should_filter_out_file(&self, path: String) -> bool {
         self.inner.borrow().should_filter_out_file(&path)
     }
 
-    pub fn add_file(&self, path: String, symbols: JsValue) -> bool {
-        let symbols = serde_wasm_bindgen::from_value::<Vec<DocumentSymbol>>(symbols).unwrap();
+    pub fn add_file(&self, path: String, symbols: JsValue) -> Result<bool, JsError> {
+        let symbols = from_js::<DocumentSymbolResponse>("symbols", symbols)?;
 
-        self.inner.borrow_mut().add_file(path, symbols)
+        Ok(self.inner.borrow_mut().add_file(path, symbols.into()))
     }
 
-    pub fn add_incoming_calls(&self, path: String, position: JsValue, calls: JsValue) {
-        let position = serde_wasm_bindgen::from_value::<Position>(position).unwrap();
-        let calls =
-            serde_wasm_bindgen::from_value::<Vec<CallHierarchyIncomingCall>>(calls).unwrap();
+    pub fn remove_file(&self, path: String) -> bool {
+        self.inner.borrow_mut().remove_file(&path)
+    }
+
+    pub fn update_file(&self, path: String, symbols: JsValue) -> Result<bool, JsError> {
+        let symbols = from_js::<DocumentSymbolResponse>("symbols", symbols)?;
+
+        Ok(self.inner.borrow_mut().update_file(path, symbols.into()))
+    }
+
+    pub fn record_file_content(&self, path: String, content: String) {
+        self.inner.borrow_mut().record_file_content(path, &content);
+    }
+
+    pub fn is_file_unchanged(&self, path: String, content: String) -> bool {
+        self.inner.borrow().is_file_unchanged(&path, &content)
+    }
+
+    pub fn save_state(&self) -> String {
+        self.inner.borrow().save_state()
+    }
+
+    /// Fails if the state is malformed or saved by an incompatible version.
+    pub fn load_state(&self, state: String) -> Result<(), JsError> {
+        Ok(self.inner.borrow_mut().load_state(&state)?)
+    }
+
+    pub fn add_incoming_calls(
+        &self,
+        path: String,
+        position: JsValue,
+        calls: JsValue,
+    ) -> Result<(), JsError> {
+        let position = from_js::<Position>("position", position)?;
+        let calls = from_js::<Vec<CallHierarchyIncomingCall>>("calls", calls)?;
 
         self.inner
             .borrow_mut()
             .add_incoming_calls(path, position, calls);
+        Ok(())
     }
 
-    pub fn add_outgoing_calls(&self, path: String, position: JsValue, calls: JsValue) {
-        let position = serde_wasm_bindgen::from_value::<Position>(position).unwrap();
-        let calls =
-            serde_wasm_bindgen::from_value::<Vec<CallHierarchyOutgoingCall>>(calls).unwrap();
+    pub fn add_outgoing_calls(
+        &self,
+        path: String,
+        position: JsValue,
+        calls: JsValue,
+    ) -> Result<(), JsError> {
+        let position = from_js::<Position>("position", position)?;
+        let calls = from_js::<Vec<CallHierarchyOutgoingCall>>("calls", calls)?;
 
         self.inner
             .borrow_mut()
             .add_outgoing_calls(path, position, calls);
+        Ok(())
     }
 
     pub fn add_interface_implementations(
@@ -76,16 +128,121 @@ impl GraphGeneratorWasm {
         path: String,
         position: JsValue,
         locations: JsValue,
-    ) {
-        let position = serde_wasm_bindgen::from_value::<Position>(position).unwrap();
-        let locations = serde_wasm_bindgen::from_value::<Vec<Location>>(locations).unwrap();
+    ) -> Result<(), JsError> {
+        let position = from_js::<Position>("position", position)?;
+        let locations = from_js::<Vec<Location>>("locations", locations)?;
 
         self.inner
             .borrow_mut()
             .add_interface_implementations(path, position, locations);
+        Ok(())
+    }
+
+    pub fn add_type_hierarchy_supertypes(
+        &self,
+        path: String,
+        position: JsValue,
+        items: JsValue,
+    ) -> Result<(), JsError> {
+        let position = from_js::<Position>("position", position)?;
+        let items = from_js::<Vec<TypeHierarchyItem>>("items", items)?;
+
+        self.inner
+            .borrow_mut()
+            .add_type_hierarchy_supertypes(path, position, items);
+        Ok(())
+    }
+
+    pub fn add_type_hierarchy_subtypes(
+        &self,
+        path: String,
+        position: JsValue,
+        items: JsValue,
+    ) -> Result<(), JsError> {
+        let position = from_js::<Position>("position", position)?;
+        let items = from_js::<Vec<TypeHierarchyItem>>("items", items)?;
+
+        self.inner
+            .borrow_mut()
+            .add_type_hierarchy_subtypes(path, position, items);
+        Ok(())
+    }
+
+    pub fn gen_graph(&self) -> Result<JsValue, JsError> {
+        Ok(to_js(&self.inner.borrow().gen_graph())?)
     }
 
-    pub fn gen_graph(&self) -> JsValue {
-        serde_wasm_bindgen::to_value(&self.inner.borrow().gen_graph()).unwrap()
+    /// Returns the graph along with the reasons files and relations are left out of it.
+    pub fn gen_graph_with_diagnostics(&self) -> Result<JsValue, JsError> {
+        Ok(to_js(&self.inner.borrow().gen_graph_with_diagnostics())?)
     }
+
+    /// Returns the graph, with the relations taking part in cycles flagged, along with the cycles.
+    pub fn gen_graph_with_cycles(&self) -> Result<JsValue, JsError> {
+        let mut graph = self.inner.borrow().gen_graph();
+        let cycles = find_cycles(&mut graph);
+
+        Ok(to_js(&(graph, cycles))?)
+    }
+
+    /// Returns the graph along with the metrics of its symbols and files,
+    /// counting transitive callers only if asked to, as it is slow on large graphs.
+    pub fn gen_graph_with_metrics(&self, transitive_callers: bool) -> Result<JsValue, JsError> {
+        let graph = self.inner.borrow().gen_graph();
+        let metrics = compute_metrics(&graph, transitive_callers);
+
+        Ok(to_js(&(graph, metrics))?)
+    }
+
+    pub fn gen_file_graph(&self) -> Result<JsValue, JsError> {
+        Ok(to_js(&self.inner.borrow().gen_file_graph())?)
+    }
+
+    pub fn gen_directory_graph(&self, depth: usize) -> Result<JsValue, JsError> {
+        Ok(to_js(&self.inner.borrow().gen_directory_graph(depth))?)
+    }
+
+    /// Compares the graph generated by `old` against the one generated by this generator.
+    pub fn diff(&self, old: &GraphGeneratorWasm) -> Result<JsValue, JsError> {
+        let diff = diff_graphs(
+            &old.inner.borrow().gen_graph(),
+            &self.inner.borrow().gen_graph(),
+        );
+
+        Ok(to_js(&diff)?)
+    }
+
+    pub fn find_dead_code(&self) -> Result<JsValue, JsError> {
+        Ok(to_js(&self.inner.borrow().find_dead_code())?)
+    }
+
+    pub fn gen_func_graph(
+        &self,
+        path: String,
+        position: JsValue,
+        max_depth: Option<u32>,
+        direction: JsValue,
+    ) -> Result<JsValue, JsError> {
+        let position = from_js::<Position>("position", position)?;
+        let direction = from_js::<FuncCallDirection>("direction", direction)?;
+
+        Ok(to_js(
+            &self
+                .inner
+                .borrow()
+                .gen_func_graph(&path, position, max_depth, direction),
+        )?)
+    }
+}
+
+/// Deserializes an argument, naming it in the error.
+fn from_js<T: DeserializeOwned>(name: &'static str, value: JsValue) -> error::Result<T> {
+    serde_wasm_bindgen::from_value(value).map_err(|err| Error::InvalidArgument {
+        name,
+        reason: err.to_string(),
+    })
+}
+
+fn to_js<T: Serialize + ?Sized>(value: &T) -> error::Result<JsValue> {
+    serde_wasm_bindgen::to_value(value).map_err(|err| Error::Serialization(err.to_string()))
 }
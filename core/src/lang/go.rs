@@ -1,4 +1,7 @@
-use super::Language;
+use {
+    super::{Language, DEFAULT_LANG},
+    crate::types::lsp::DocumentSymbol,
+};
 
 pub(crate) struct Go;
 
@@ -6,4 +9,39 @@ impl Language for Go {
     fn should_filter_out_file(&self, file: &str) -> bool {
         file.ends_with("_test.go")
     }
+
+    fn qualified_name(&self, file: &str, parent: Option<&str>, name: &str) -> String {
+        let parent = parent.or_else(|| self.module_path(file).last().copied());
+
+        match parent {
+            Some(parent) => format!("{parent}.{name}"),
+            None => name.to_string(),
+        }
+    }
+
+    fn is_entry_point(
+        &self,
+        _file: &str,
+        symbol: &DocumentSymbol,
+        _parent: Option<&DocumentSymbol>,
+    ) -> bool {
+        // methods are named like `(*Server).Serve`
+        let name = symbol.name.rsplit('.').next().unwrap_or(&symbol.name);
+
+        matches!(name, "main" | "init") || name.starts_with(char::is_uppercase)
+    }
+
+    fn external_package<'a>(&self, file: &'a str) -> (&'a str, &'a str) {
+        // modules are in `pkg/mod/<module path>@<version>`
+        let module = file.rsplit_once("/pkg/mod/").and_then(|(prefix, rest)| {
+            let version = rest.find('@')?;
+            let end = version + rest[version..].find('/')?;
+            Some((
+                &rest[..end],
+                &file[..prefix.len() + "/pkg/mod/".len() + end],
+            ))
+        });
+
+        module.unwrap_or_else(|| DEFAULT_LANG.external_package(file))
+    }
 }
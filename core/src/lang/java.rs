@@ -0,0 +1,27 @@
+use {
+    super::Language,
+    crate::types::lsp::{DocumentSymbol, SymbolKind},
+};
+
+pub(crate) struct Java;
+
+impl Language for Java {
+    fn is_entry_point(
+        &self,
+        file: &str,
+        symbol: &DocumentSymbol,
+        parent: Option<&DocumentSymbol>,
+    ) -> bool {
+        // jdtls names methods with their parameters, like `main(String[])`
+        let name = symbol.name.split('(').next().unwrap_or(&symbol.name);
+
+        name == "main"
+            // called by the runtime and the collections
+            || matches!(name, "toString" | "equals" | "hashCode" | "compareTo" | "run" | "call" | "close")
+            // tests run by JUnit
+            || file.contains("/src/test/")
+            || parent.is_some_and(|p| {
+                p.kind == SymbolKind::Class && (p.name.ends_with("Test") || p.name.ends_with("Tests"))
+            })
+    }
+}
@@ -17,4 +17,18 @@ impl Language for Jsts {
             _ => DEFAULT_LANG.filter_symbol(symbol, parent),
         }
     }
+
+    fn is_entry_point(
+        &self,
+        file: &str,
+        symbol: &DocumentSymbol,
+        _parent: Option<&DocumentSymbol>,
+    ) -> bool {
+        // test files are run by the test runner, whose callbacks are not named
+        symbol.name == "main"
+            || symbol.name == "constructor"
+            || [".test.", ".spec.", "/__tests__/"]
+                .iter()
+                .any(|marker| file.contains(marker))
+    }
 }
@@ -1,9 +1,11 @@
 mod go;
+mod java;
 mod jsts;
+mod python;
 mod rust;
 
 use {
-    self::{go::Go, jsts::Jsts, rust::Rust},
+    self::{go::Go, java::Java, jsts::Jsts, python::Python, rust::Rust},
     crate::types::lsp::{DocumentSymbol, SymbolKind},
 };
 
@@ -22,18 +24,79 @@ pub(crate) trait Language {
         }
     }
 
+    /// Whether the symbol may be called from outside of the analyzed code,
+    /// so that it is not reported as dead code.
+    fn is_entry_point(
+        &self,
+        _file: &str,
+        symbol: &DocumentSymbol,
+        _parent: Option<&DocumentSymbol>,
+    ) -> bool {
+        symbol.name == "main"
+    }
+
+    /// Joins the qualified name of the container, if any, with the name of a symbol.
+    fn qualified_name(&self, _file: &str, parent: Option<&str>, name: &str) -> String {
+        match parent {
+            Some(parent) => format!("{parent}.{name}"),
+            None => name.to_string(),
+        }
+    }
+
+    /// Path components of the package or module the file belongs to,
+    /// which is the directory of the file by default.
+    fn module_path<'a>(&self, file: &'a str) -> Vec<&'a str> {
+        let mut components = file
+            .split('/')
+            .filter(|c| !c.is_empty())
+            .collect::<Vec<_>>();
+        components.pop();
+        components
+    }
+
+    /// The name and the root directory of the library or package a file outside of the analyzed code
+    /// belongs to, which is the package under `node_modules` or the directory of the file by default.
+    fn external_package<'a>(&self, file: &'a str) -> (&'a str, &'a str) {
+        node_modules_package(file).unwrap_or_else(|| parent_directory(file))
+    }
+
     // fn handle_unrecognized_functions(&self, funcs: Vec<&DocumentSymbol>);
 }
 
+/// Returns the `len` path components following the last `marker` in the path,
+/// and the path up to them, if there is a file below.
+fn package_after<'a>(file: &'a str, marker: &str, len: usize) -> Option<(&'a str, &'a str)> {
+    let start = file.rfind(marker)? + marker.len();
+    let mut end = start;
+    for _ in 0..len {
+        end += file[end..].find('/')? + 1;
+    }
+
+    Some((&file[start..end - 1], &file[..end - 1]))
+}
+
+fn node_modules_package(file: &str) -> Option<(&str, &str)> {
+    let scoped = file.rsplit_once("/node_modules/")?.1.starts_with('@');
+    package_after(file, "/node_modules/", if scoped { 2 } else { 1 })
+}
+
+fn parent_directory(file: &str) -> (&str, &str) {
+    let root = file.rsplit_once('/').map_or("", |(dir, _)| dir);
+    let name = root.rsplit('/').next().unwrap_or(root);
+    (name, root)
+}
+
 pub struct DefaultLang;
 impl Language for DefaultLang {}
 
-pub(self) const DEFAULT_LANG: DefaultLang = DefaultLang {};
+const DEFAULT_LANG: DefaultLang = DefaultLang {};
 
 pub(crate) fn language_handler(lang: &str) -> Box<dyn Language + Sync + Send> {
     match lang {
         "Go" => Box::new(Go),
         "Rust" => Box::new(Rust),
+        "Python" => Box::new(Python),
+        "Java" => Box::new(Java),
         "JavaScript" | "TypeScript" | "JavaScript JSX" | "TypeScript JSX" => Box::new(Jsts),
         _ => Box::new(DEFAULT_LANG),
     }
@@ -0,0 +1,31 @@
+use {
+    super::Language,
+    crate::types::lsp::{DocumentSymbol, SymbolKind},
+};
+
+pub(crate) struct Python;
+
+impl Language for Python {
+    fn is_entry_point(
+        &self,
+        file: &str,
+        symbol: &DocumentSymbol,
+        parent: Option<&DocumentSymbol>,
+    ) -> bool {
+        let name = symbol.name.as_str();
+        let file_name = file.rsplit('/').next().unwrap_or(file);
+
+        name == "main"
+            // special methods are called by the interpreter
+            || (name.starts_with("__") && name.ends_with("__"))
+            // tests collected by pytest and unittest
+            || (is_test_file(file_name) && name.starts_with("test"))
+            || matches!(name, "setUp" | "tearDown" | "setUpClass" | "tearDownClass")
+            || parent.is_some_and(|p| p.kind == SymbolKind::Class && p.name.starts_with("Test"))
+            || (file_name == "conftest.py" && parent.is_none())
+    }
+}
+
+fn is_test_file(file_name: &str) -> bool {
+    file_name.starts_with("test_") || file_name.ends_with("_test.py")
+}
@@ -1,7 +1,7 @@
 use {
     super::Language,
     crate::{
-        lang::DEFAULT_LANG,
+        lang::{package_after, DEFAULT_LANG},
         types::lsp::{DocumentSymbol, SymbolKind},
     },
 };
@@ -17,4 +17,114 @@ impl Language for Rust {
             _ => DEFAULT_LANG.filter_symbol(symbol, parent),
         }
     }
+
+    fn is_entry_point(
+        &self,
+        file: &str,
+        symbol: &DocumentSymbol,
+        parent: Option<&DocumentSymbol>,
+    ) -> bool {
+        // rust-analyzer reports neither visibility nor attributes,
+        // so tests are told by their names and locations
+        symbol.name == "main"
+            || symbol.name == "test"
+            || symbol.name.starts_with("test_")
+            || parent.is_some_and(|p| p.kind == SymbolKind::Module && p.name == "tests")
+            || (parent.is_none() && is_test_target(file))
+            // trait methods are mostly called through the trait
+            || parent
+                .and_then(|p| parse_impl(&p.name))
+                .is_some_and(|(trait_name, _)| trait_name.is_some())
+    }
+
+    fn qualified_name(&self, file: &str, parent: Option<&str>, name: &str) -> String {
+        // items of impl blocks are qualified by the type
+        let name = parse_impl(name).map_or(name, |(_, ty)| ty);
+
+        match parent {
+            Some(parent) => format!("{parent}::{name}"),
+            None => {
+                // module path relative to the closest `src` directory
+                let module_path = self.module_path(file);
+                let start = module_path
+                    .iter()
+                    .rposition(|c| *c == "src")
+                    .map_or(module_path.len().saturating_sub(1), |i| i + 1);
+
+                module_path[start..]
+                    .iter()
+                    .copied()
+                    .chain([name])
+                    .collect::<Vec<_>>()
+                    .join("::")
+            }
+        }
+    }
+
+    fn external_package<'a>(&self, file: &'a str) -> (&'a str, &'a str) {
+        // crates from registries are in `registry/src/<index>/<crate>-<version>`,
+        // and from git in `git/checkouts/<repository>/<revision>`
+        let package = package_after(file, "/.cargo/registry/src/", 2)
+            .map(|(name, root)| (name.rsplit('/').next().unwrap_or(name), root))
+            .or_else(|| {
+                package_after(file, "/.cargo/git/checkouts/", 2)
+                    .map(|(name, root)| (name.split('/').next().unwrap_or(name), root))
+            })
+            .or_else(|| package_after(file, "/rustlib/src/rust/library/", 1));
+
+        package.unwrap_or_else(|| DEFAULT_LANG.external_package(file))
+    }
+
+    fn module_path<'a>(&self, file: &'a str) -> Vec<&'a str> {
+        let mut components = file
+            .split('/')
+            .filter(|c| !c.is_empty())
+            .collect::<Vec<_>>();
+
+        match components.pop() {
+            Some("mod.rs" | "lib.rs" | "main.rs") | None => {}
+            Some(name) => components.push(name.strip_suffix(".rs").unwrap_or(name)),
+        }
+        components
+    }
+}
+
+/// Splits the name rust-analyzer gives to impl blocks, like `impl<T> Trait for Type<T>`,
+/// into the trait, if any, and the type.
+fn parse_impl(name: &str) -> Option<(Option<&str>, &str)> {
+    let rest = name.strip_prefix("impl")?;
+    let rest = match rest.strip_prefix('<') {
+        // skip the generic parameters, whose bounds may contain `<>` or `->`
+        Some(params) => {
+            let mut depth = 1;
+            let mut prev = '<';
+            let end = params.char_indices().find_map(|(i, c)| {
+                match c {
+                    '<' => depth += 1,
+                    '>' if prev != '-' => depth -= 1,
+                    _ => {}
+                }
+                prev = c;
+                (depth == 0).then_some(i + 1)
+            })?;
+            &params[end..]
+        }
+        None => rest,
+    };
+    if !rest.starts_with(' ') {
+        return None;
+    }
+
+    let rest = rest.trim();
+    Some(match rest.split_once(" for ") {
+        Some((trait_name, ty)) => (Some(trait_name.trim()), ty.trim()),
+        None => (None, rest),
+    })
+}
+
+/// Whether the file is an integration test or a benchmark, whose functions are run by the harness.
+fn is_test_target(file: &str) -> bool {
+    ["/tests/", "/benches/"]
+        .iter()
+        .any(|dir| file.contains(dir))
 }
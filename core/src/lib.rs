@@ -1,5 +1,14 @@
+mod analysis;
+mod error;
 mod generator;
 mod lang;
 mod types;
 
-pub use generator::GraphGenerator;
+pub use {
+    analysis::{
+        compute_metrics, diff_graphs, find_cycles, Cycles, FileMetrics, GraphDiff, GraphMetrics,
+        Metrics, RelationIdentity, SymbolMetrics, UnusedSymbol,
+    },
+    error::Error,
+    generator::{FuncCallDirection, GraphGenerator, SymbolFilter},
+};
@@ -1,54 +1,287 @@
 use {
-    super::lsp::{Position, Range, SymbolKind},
-    serde::Serialize,
-    serde_repr::Serialize_repr,
-    std::hash::{Hash, Hasher},
+    super::lsp::{Position, Range, SymbolKind, SymbolTag},
+    serde::{Deserialize, Serialize},
+    serde_repr::{Deserialize_repr, Serialize_repr},
+    std::{
+        cmp::Ordering,
+        hash::{Hash, Hasher},
+    },
 };
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Deserialize, Serialize)]
 pub struct Graph {
+    /// The workspace roots containing files of the graph, in the order they were added.
+    #[serde(default)]
+    pub roots: Vec<WorkspaceRoot>,
     pub files: Vec<File>,
+    /// The libraries and packages outside of the analyzed files that are called,
+    /// if external calls are kept. Their files are numbered after the analyzed files.
+    #[serde(default)]
+    pub externals: Vec<ExternalPackage>,
     pub relations: Vec<Relation>,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Deserialize, Serialize)]
+pub struct ExternalPackage {
+    pub name: String,
+    /// The location of the package, which is not serialized, as it differs between machines.
+    #[serde(skip)]
+    pub path: String,
+    /// The files of the package, with only the called symbols, and paths relative to the package.
+    pub files: Vec<File>,
+}
+
+/// A named workspace folder, which groups the files under it.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct WorkspaceRoot {
+    pub name: String,
+    /// The location of the folder, which is not serialized, as it differs between checkouts.
+    #[serde(skip)]
+    pub path: String,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
 pub struct File {
     pub id: u32,
+    /// The full path, which is not serialized, leaving the [workspace path](File::workspace_path)
+    /// to identify the file.
+    #[serde(skip)]
     pub path: String,
+    /// The name of the innermost workspace root containing the file.
+    pub root: Option<String>,
+    /// The path relative to the root, or the full path for files outside of every root.
+    pub relative_path: String,
     pub symbols: Vec<Symbol>,
 }
 
-#[derive(Debug, Serialize)]
+impl File {
+    /// The path prefixed by the name of the root instead of its location, which stays the same
+    /// across checkouts of the workspace, or the full path for files outside of every root.
+    pub fn workspace_path(&self) -> String {
+        File::make_workspace_path(self.root.as_deref(), &self.relative_path)
+    }
+
+    pub fn make_workspace_path(root: Option<&str>, relative_path: &str) -> String {
+        match root {
+            Some(root) => format!("{root}/{relative_path}"),
+            None => relative_path.to_string(),
+        }
+    }
+
+    /// Returns the innermost symbol whose full range contains the position.
+    pub fn symbol_at(&self, position: Position) -> Option<&Symbol> {
+        let mut symbols = &self.symbols;
+        let mut found = None;
+
+        while let Some(symbol) = symbols
+            .iter()
+            .find(|symbol| symbol.range.start <= position && position < symbol.range.end)
+        {
+            found = Some(symbol);
+            symbols = &symbol.children;
+        }
+
+        found
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
 pub struct Symbol {
+    /// Built from the file path and the kinds and names of the symbol and its containers,
+    /// so that it stays the same when lines above the symbol are edited.
+    pub id: String,
     pub name: String,
+    /// The name prefixed with the names of the containers, formatted per language.
+    pub qualified_name: String,
+    /// More detail for the symbol, e.g. the signature of a function.
+    pub detail: Option<String>,
     pub kind: SymbolKind,
+    pub tags: Vec<SymbolTag>,
+    /// The full extent of the symbol, including its body.
     pub range: Range,
+    /// The identifier of the symbol, whose start is used as the symbol's position.
+    pub selection_range: Range,
     pub children: Vec<Symbol>,
 }
 
-#[derive(Debug, Clone, Serialize)]
+impl Symbol {
+    /// Builds the id of a top-level symbol from the [workspace path](File::workspace_path) of its file.
+    /// `overload` returns how many symbols already have the id, to tell overloads apart.
+    pub fn make_top_level_id(
+        file_path: &str,
+        kind: SymbolKind,
+        name: &str,
+        overload: impl FnOnce(&str) -> u32,
+    ) -> String {
+        Symbol::with_overload(format!("{file_path}#{}", id_segment(kind, name)), overload)
+    }
+
+    /// Builds the id of a symbol from the id of its container.
+    pub fn make_child_id(
+        parent_id: &str,
+        kind: SymbolKind,
+        name: &str,
+        overload: impl FnOnce(&str) -> u32,
+    ) -> String {
+        Symbol::with_overload(format!("{parent_id}/{}", id_segment(kind, name)), overload)
+    }
+
+    fn with_overload(id: String, overload: impl FnOnce(&str) -> u32) -> String {
+        match overload(&id) {
+            0 => id,
+            n => format!("{id}~{n}"),
+        }
+    }
+
+    pub fn is_overload_of(id: &str, base_id: &str) -> bool {
+        id.strip_prefix(base_id)
+            .is_some_and(|rest| rest.is_empty() || rest.starts_with('~'))
+    }
+}
+
+/// The part of an id naming a symbol, where the characters separating the parts of ids are escaped,
+/// as in `operator/` or `~Widget`. The file path is separated by the last `#` of the id.
+fn id_segment(kind: SymbolKind, name: &str) -> String {
+    let mut segment = format!("{}:", kind as u8);
+    for c in name.chars() {
+        match c {
+            '%' => segment.push_str("%25"),
+            '/' => segment.push_str("%2F"),
+            '#' => segment.push_str("%23"),
+            '~' => segment.push_str("%7E"),
+            c => segment.push(c),
+        }
+    }
+    segment
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
 pub struct Relation {
     pub from: GlobalPosition,
     pub to: GlobalPosition,
+    pub from_id: String,
+    pub to_id: String,
     pub kind: RelationKind,
+    /// Call sites in the file of `from`, empty for relations other than calls.
+    pub ranges: Vec<Range>,
+    /// Number of distinct call sites.
+    pub count: u32,
+    /// Set by [`find_cycles`](crate::analysis::find_cycles) when both ends are in the same group of mutually recursive symbols.
+    pub in_call_cycle: bool,
+    /// Set by [`find_cycles`](crate::analysis::find_cycles) when it links two files depending on each other.
+    pub in_file_cycle: bool,
+}
+
+impl Relation {
+    pub fn new(
+        from: GlobalPosition,
+        to: GlobalPosition,
+        kind: RelationKind,
+        ranges: Vec<Range>,
+    ) -> Self {
+        Self {
+            from,
+            to,
+            from_id: String::new(),
+            to_id: String::new(),
+            kind,
+            count: ranges.len() as u32,
+            ranges,
+            in_call_cycle: false,
+            in_file_cycle: false,
+        }
+    }
 }
 
 impl Hash for Relation {
     fn hash<H: Hasher>(&self, state: &mut H) {
         self.from.hash(state);
         self.to.hash(state);
+        self.kind.hash(state);
     }
 }
 
 impl PartialEq for Relation {
     fn eq(&self, other: &Self) -> bool {
-        self.from == other.from && self.to == other.to
+        self.from == other.from && self.to == other.to && self.kind == other.kind
     }
 }
 
 impl Eq for Relation {}
 
-#[derive(Debug, Clone, Serialize_repr)]
+impl PartialOrd for Relation {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Relation {
+    fn cmp(&self, other: &Self) -> Ordering {
+        (self.from, self.to, self.kind).cmp(&(other.from, other.to, other.kind))
+    }
+}
+
+/// A graph of files, or of directories, summarizing the relations between their symbols.
+#[derive(Debug, Serialize)]
+pub struct DependencyGraph {
+    pub nodes: Vec<DependencyNode>,
+    pub edges: Vec<Dependency>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DependencyNode {
+    pub id: u32,
+    /// The [workspace path](File::workspace_path) of the file, or the module path of the group.
+    pub path: String,
+    /// Number of symbols, nested ones included.
+    pub symbol_count: u32,
+    pub file_count: u32,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Dependency {
+    pub from: u32,
+    pub to: u32,
+    /// Number of symbol relations summarized by this dependency.
+    pub weight: u32,
+    #[serde(flatten)]
+    pub counts: RelationCounts,
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct RelationCounts {
+    pub calls: u32,
+    pub impls: u32,
+    pub inherits: u32,
+}
+
+impl RelationCounts {
+    pub fn add(&mut self, kind: &RelationKind) {
+        match kind {
+            RelationKind::Call => self.calls += 1,
+            RelationKind::Impl => self.impls += 1,
+            RelationKind::Inherit => self.inherits += 1,
+        }
+    }
+
+    pub fn merge(&mut self, other: &RelationCounts) {
+        self.calls += other.calls;
+        self.impls += other.impls;
+        self.inherits += other.inherits;
+    }
+
+    pub fn total(&self) -> u32 {
+        self.calls + self.impls + self.inherits
+    }
+}
+
+#[derive(
+    Debug, Clone, Copy, Hash, PartialEq, Eq, PartialOrd, Ord, Deserialize_repr, Serialize_repr,
+)]
 #[repr(u8)]
 pub enum RelationKind {
     Call,
@@ -56,7 +289,7 @@ pub enum RelationKind {
     Inherit,
 }
 
-#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq, Serialize)]
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq, PartialOrd, Ord, Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct GlobalPosition {
     pub file_id: u32,
@@ -72,4 +305,66 @@ impl GlobalPosition {
             character: position.character,
         }
     }
+
+    pub fn with_file_id(&self, file_id: u32) -> Self {
+        Self { file_id, ..*self }
+    }
+}
+
+/// A position in a file, for files that may not be in the graph.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Deserialize, Serialize)]
+pub struct PathPosition {
+    pub path: String,
+    pub position: Position,
+}
+
+/// Explains what was left out of a graph.
+#[derive(Debug, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Diagnostics {
+    pub rejected_files: Vec<RejectedFile>,
+    /// Calls to or from symbols in files that have not been added.
+    pub unresolved_calls: Vec<UnresolvedCall>,
+    pub dropped_relations: Vec<DroppedRelation>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+pub struct RejectedFile {
+    pub path: String,
+    pub reason: RejectReason,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum RejectReason {
+    /// The language filters out the file, e.g. as it contains tests.
+    FilteredOut,
+    /// The file has already been added.
+    Duplicate,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+pub struct UnresolvedCall {
+    pub from: PathPosition,
+    pub to: PathPosition,
+    /// The name of the symbol in the file that has not been added.
+    pub name: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+pub struct DroppedRelation {
+    pub from: PathPosition,
+    pub to: PathPosition,
+    pub kind: RelationKind,
+    pub reason: DropReason,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum DropReason {
+    /// No symbol starts at the source, e.g. as it has been filtered out,
+    /// and it is not enclosed by a function it could be nested in.
+    UnknownSource,
+    /// No symbol starts at the target, e.g. as it has been filtered out.
+    UnknownTarget,
 }
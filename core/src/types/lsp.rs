@@ -1,12 +1,12 @@
 //! Some Language Server Protocol types used in crabviz, copied from gluon-lang/lsp-types with some modifications.
 
 use {
-    serde::{Deserialize, Serialize},
+    serde::{Deserialize, Deserializer, Serialize, Serializer},
     serde_json::Value,
-    serde_repr::Serialize_repr,
+    serde_repr::{Deserialize_repr, Serialize_repr},
 };
 
-#[derive(Debug, Eq, PartialEq, Copy, Clone, Deserialize, Serialize_repr)]
+#[derive(Debug, Eq, PartialEq, Ord, PartialOrd, Copy, Clone, Hash, Deserialize, Serialize_repr)]
 #[serde(from = "i32")]
 #[repr(u8)]
 pub enum SymbolKind {
@@ -40,9 +40,6 @@ pub enum SymbolKind {
 
 impl From<i32> for SymbolKind {
     fn from(value: i32) -> Self {
-        #[cfg(feature = "vscode")]
-        let value = value + 1;
-
         match value {
             1 => SymbolKind::File,
             2 => SymbolKind::Module,
@@ -75,7 +72,37 @@ impl From<i32> for SymbolKind {
     }
 }
 
-#[derive(Debug, Eq, PartialEq, Copy, Clone, Deserialize)]
+impl std::str::FromStr for SymbolKind {
+    type Err = String;
+
+    /// Parses the name of a kind, such as `EnumMember`, ignoring case.
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        (1..=26)
+            .map(SymbolKind::from)
+            .find(|kind| format!("{kind:?}").eq_ignore_ascii_case(name))
+            .ok_or_else(|| format!("unknown symbol kind `{name}`"))
+    }
+}
+
+/// (De)serializes symbol kinds sent by the editor, which are zero-based in VS Code.
+mod client_symbol_kind {
+    use super::*;
+
+    #[cfg(feature = "vscode")]
+    const OFFSET: i32 = 1;
+    #[cfg(not(feature = "vscode"))]
+    const OFFSET: i32 = 0;
+
+    pub fn serialize<S: Serializer>(kind: &SymbolKind, serializer: S) -> Result<S::Ok, S::Error> {
+        (*kind as i32 - OFFSET).serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<SymbolKind, D::Error> {
+        Ok(SymbolKind::from(i32::deserialize(deserializer)? + OFFSET))
+    }
+}
+
+#[derive(Debug, Eq, PartialEq, Copy, Clone, Deserialize_repr, Serialize_repr)]
 #[repr(u8)]
 pub enum SymbolTag {
     /**
@@ -102,7 +129,9 @@ pub struct Position {
 
 /// A range in a text document expressed as (zero-based) start and end positions.
 /// A range is comparable to a selection in an editor. Therefore the end position is exclusive.
-#[derive(Debug, Eq, PartialEq, Copy, Clone, Default, Deserialize, Serialize)]
+#[derive(
+    Debug, Eq, PartialEq, Ord, PartialOrd, Copy, Clone, Default, Deserialize, Hash, Serialize,
+)]
 pub struct Range {
     /// The range's start position.
     pub start: Position,
@@ -110,7 +139,7 @@ pub struct Range {
     pub end: Position,
 }
 
-#[derive(Debug, Eq, PartialEq, Clone, Deserialize)]
+#[derive(Debug, Eq, PartialEq, Clone, Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct DocumentSymbol {
     /// The name of this symbol.
@@ -120,6 +149,7 @@ pub struct DocumentSymbol {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub detail: Option<String>,
     /// The kind of this symbol.
+    #[serde(with = "client_symbol_kind")]
     pub kind: SymbolKind,
     /// Tags for this completion item.
     ///  since 3.16.0
@@ -137,13 +167,82 @@ pub struct DocumentSymbol {
     pub children: Vec<DocumentSymbol>,
 }
 
-#[derive(Deserialize, Debug, PartialEq, Clone)]
+/// Represents information about programming constructs like variables, classes,
+/// interfaces etc.
+#[derive(Debug, Eq, PartialEq, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SymbolInformation {
+    /// The name of this symbol.
+    pub name: String,
+    /// The kind of this symbol.
+    #[serde(with = "client_symbol_kind")]
+    pub kind: SymbolKind,
+    /// Tags for this symbol.
+    ///  since 3.16.0
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tags: Option<Vec<SymbolTag>>,
+    /// Indicates if this symbol is deprecated.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub deprecated: Option<bool>,
+    /// The location of this symbol. The range is the range enclosing the symbol, not only its name.
+    pub location: Location,
+    /// The name of the symbol containing this symbol. This information is for user interface
+    /// purposes and can't be used to re-infer a hierarchy for the document symbols.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub container_name: Option<String>,
+}
+
+/// The result of a document symbol request, either hierarchical or flat.
+#[derive(Debug, PartialEq, Clone, Serialize)]
+#[serde(untagged)]
+pub enum DocumentSymbolResponse {
+    Nested(Vec<DocumentSymbol>),
+    Flat(Vec<SymbolInformation>),
+}
+
+impl<'de> Deserialize<'de> for DocumentSymbolResponse {
+    /// Picks the form by the fields of the first symbol, so that a malformed symbol is reported
+    /// with the error of its form, rather than as matching neither form.
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        use serde::de::Error;
+
+        let symbols = Vec::<Value>::deserialize(deserializer)?;
+        let Some(first) = symbols.first() else {
+            return Ok(Self::Nested(vec![]));
+        };
+        let (is_nested, is_flat) = (
+            first.get("selectionRange").is_some(),
+            first.get("location").is_some(),
+        );
+
+        let symbols = Value::Array(symbols);
+        let nested = || serde_json::from_value(symbols.clone()).map(Self::Nested);
+        let flat = || serde_json::from_value(symbols.clone()).map(Self::Flat);
+
+        if is_nested {
+            nested().map_err(|e| D::Error::custom(format!("invalid document symbols: {e}")))
+        } else if is_flat {
+            flat().map_err(|e| D::Error::custom(format!("invalid symbol information: {e}")))
+        } else {
+            nested().or_else(|nested_err| {
+                flat().map_err(|flat_err| {
+                    D::Error::custom(format!(
+                        "neither document symbols ({nested_err}) nor symbol information ({flat_err})"
+                    ))
+                })
+            })
+        }
+    }
+}
+
+#[derive(Deserialize, Debug, PartialEq, Clone, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct CallHierarchyItem {
     /// The name of this item.
     pub name: String,
 
     /// The kind of this item.
+    #[serde(with = "client_symbol_kind")]
     pub kind: SymbolKind,
 
     /// Tags for this item.
@@ -170,7 +269,7 @@ pub struct CallHierarchyItem {
 }
 
 /// Represents an incoming call, e.g. a caller of a method or constructor.
-#[derive(Deserialize, Debug, PartialEq, Clone)]
+#[derive(Deserialize, Debug, PartialEq, Clone, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct CallHierarchyIncomingCall {
     /// The item that makes the call.
@@ -182,7 +281,7 @@ pub struct CallHierarchyIncomingCall {
 }
 
 /// Represents an outgoing call, e.g. calling a getter from a method or a method from a constructor etc.
-#[derive(Deserialize, Debug, PartialEq, Clone)]
+#[derive(Deserialize, Debug, PartialEq, Clone, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct CallHierarchyOutgoingCall {
     /// The item that is called.
@@ -194,20 +293,256 @@ pub struct CallHierarchyOutgoingCall {
     pub from_ranges: Vec<Range>,
 }
 
-#[derive(Deserialize, Debug, PartialEq, Clone, Eq)]
+/// Represents an item of the type hierarchy, e.g. a class or an interface.
+///  since 3.17.0
+#[derive(Deserialize, Debug, PartialEq, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TypeHierarchyItem {
+    /// The name of this item.
+    pub name: String,
+
+    /// The kind of this item.
+    #[serde(with = "client_symbol_kind")]
+    pub kind: SymbolKind,
+
+    /// Tags for this item.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tags: Option<Vec<SymbolTag>>,
+
+    /// More detail for this item, e.g. the signature of a function.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub detail: Option<String>,
+
+    /// The resource identifier of this item.
+    pub uri: Uri,
+
+    /// The range enclosing this symbol not including leading/trailing whitespace
+    /// but everything else, e.g. comments and code.
+    pub range: Range,
+
+    /// The range that should be selected and revealed when this symbol is being
+    /// picked, e.g. the name of a function. Must be contained by the
+    /// [`range`](#TypeHierarchyItem.range).
+    pub selection_range: Range,
+
+    /// A data entry field that is preserved between a type hierarchy prepare and
+    /// supertypes or subtypes requests.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub data: Option<Value>,
+}
+
+/// A URI, parsed from either its string form or the components the editor sends.
+#[derive(Deserialize, Debug, PartialEq, Clone, Eq, Serialize)]
+#[serde(from = "UriRepr")]
 pub struct Uri {
+    pub scheme: String,
+    pub authority: String,
+    /// The percent-decoded path.
     pub path: String,
+    pub query: String,
+    pub fragment: String,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum UriRepr {
+    String(String),
+    Components {
+        #[serde(default)]
+        scheme: String,
+        #[serde(default)]
+        authority: String,
+        path: String,
+        #[serde(default)]
+        query: String,
+        #[serde(default)]
+        fragment: String,
+    },
+}
+
+impl From<UriRepr> for Uri {
+    fn from(repr: UriRepr) -> Self {
+        match repr {
+            UriRepr::String(uri) => Uri::parse(&uri),
+            UriRepr::Components {
+                scheme,
+                authority,
+                path,
+                query,
+                fragment,
+            } => Uri {
+                scheme: scheme.to_ascii_lowercase(),
+                authority,
+                path,
+                query,
+                fragment,
+            },
+        }
+    }
+}
+
+impl Uri {
+    /// Parses a URI such as `file:///c%3A/src/main.rs` or `untitled:Untitled-1`.
+    /// Strings without a scheme, including Windows paths like `C:\src\main.rs`, are taken as file paths.
+    pub fn parse(uri: &str) -> Self {
+        let Some((scheme, rest)) = uri.split_once(':').filter(|(scheme, _)| is_scheme(scheme))
+        else {
+            let path = uri.replace('\\', "/");
+            let (authority, path) = match path.strip_prefix("//") {
+                Some(unc) => split_authority(unc),
+                None => ("", path.as_str()),
+            };
+
+            return Uri {
+                scheme: "file".to_string(),
+                authority: authority.to_string(),
+                path: path.to_string(),
+                query: String::new(),
+                fragment: String::new(),
+            };
+        };
+
+        let (rest, fragment) = rest.split_once('#').unwrap_or((rest, ""));
+        let (rest, query) = rest.split_once('?').unwrap_or((rest, ""));
+        let (authority, path) = match rest.strip_prefix("//") {
+            Some(rest) => split_authority(rest),
+            None => ("", rest),
+        };
+
+        Uri {
+            scheme: scheme.to_ascii_lowercase(),
+            authority: percent_decode(authority),
+            path: percent_decode(path),
+            query: percent_decode(query),
+            fragment: percent_decode(fragment),
+        }
+    }
+
+    pub fn is_file(&self) -> bool {
+        self.scheme.is_empty() || self.scheme == "file"
+    }
+
+    /// Spells the URI the same way however it was written.
+    ///
+    /// Files are spelled as paths with `/` separators, without `.` and `..` segments,
+    /// and with upper-case drive letters following a `/`, e.g. `/C:/src/main.rs`.
+    /// Other schemes are kept, e.g. `untitled:Untitled-1`, so they never collide with files.
+    pub fn canonical(&self) -> String {
+        if !self.is_file() {
+            let mut uri = format!("{}:", self.scheme);
+            if !self.authority.is_empty() {
+                uri += &format!("//{}", self.authority);
+            }
+            uri += &self.path;
+            if !self.query.is_empty() {
+                uri += &format!("?{}", self.query);
+            }
+            if !self.fragment.is_empty() {
+                uri += &format!("#{}", self.fragment);
+            }
+            return uri;
+        }
+
+        let path = self.path.replace('\\', "/");
+        let mut segments = path.split('/').filter(|s| !s.is_empty() && *s != ".");
+
+        let mut prefix = match self.authority.as_str() {
+            "" if path.starts_with('/') => "/".to_string(),
+            "" => String::new(),
+            authority => format!("//{authority}/"),
+        };
+        let mut normalized: Vec<&str> = vec![];
+
+        let mut first = segments.next();
+        if let Some(drive) = first.filter(|s| is_drive(s)) {
+            prefix = format!("/{}/", drive.to_ascii_uppercase());
+            first = None;
+        }
+
+        for segment in first.into_iter().chain(segments) {
+            match segment {
+                ".." if normalized.last().is_some_and(|s| *s != "..") => {
+                    normalized.pop();
+                }
+                ".." if !prefix.is_empty() => {}
+                segment => normalized.push(segment),
+            }
+        }
+
+        prefix + &normalized.join("/")
+    }
+
+    /// The canonical spelling, case-folded where paths are case-insensitive by convention,
+    /// i.e. for Windows drive and UNC paths.
+    pub fn key(&self) -> String {
+        let canonical = self.canonical();
+
+        if self.is_file()
+            && (!self.authority.is_empty() || canonical.get(1..3).is_some_and(is_drive))
+        {
+            canonical.to_lowercase()
+        } else {
+            canonical
+        }
+    }
+}
+
+fn is_scheme(s: &str) -> bool {
+    // a single letter is a drive letter
+    s.len() > 1
+        && s.starts_with(|c: char| c.is_ascii_alphabetic())
+        && s.chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '+' | '-' | '.'))
+}
+
+fn is_drive(s: &str) -> bool {
+    let s = s.as_bytes();
+    s.len() == 2 && s[0].is_ascii_alphabetic() && s[1] == b':'
+}
+
+fn split_authority(s: &str) -> (&str, &str) {
+    match s.find('/') {
+        Some(i) => s.split_at(i),
+        None => (s, ""),
+    }
+}
+
+fn percent_decode(s: &str) -> String {
+    let bytes = s.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+
+    let mut i = 0;
+    while i < bytes.len() {
+        let byte = s
+            .get(i + 1..i + 3)
+            .filter(|_| bytes[i] == b'%')
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+
+        match byte {
+            Some(byte) => {
+                decoded.push(byte);
+                i += 3;
+            }
+            None => {
+                decoded.push(bytes[i]);
+                i += 1;
+            }
+        }
+    }
+
+    String::from_utf8_lossy(&decoded).into_owned()
 }
 
 /// Represents a location inside a resource, such as a line inside a text file.
-#[derive(Debug, Eq, PartialEq, Clone, Deserialize)]
+#[derive(Debug, Eq, PartialEq, Clone, Deserialize, Serialize)]
 pub struct Location {
     pub uri: Uri,
     pub range: Range,
 }
 
 /// Represents a link between a source and a target location.
-#[derive(Debug, Eq, PartialEq, Clone, Deserialize)]
+#[allow(dead_code)]
+#[derive(Debug, Eq, PartialEq, Clone, Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct LocationLink {
     /// Span of the origin of this link.
@@ -4,9 +4,10 @@ import { Ignore } from 'ignore';
 
 import { readIgnores } from './utils/ignore';
 import { FileClassifier } from './utils/file-classifier';
-import { Generator } from './generator';
+import { Generator, SymbolFilter } from './generator';
 import { CallGraphPanel } from './webview';
 import { getLanguages } from './utils/languages';
+import { toWorkspacePath } from './utils/workspace-path';
 
 export class CommandManager {
   private context: vscode.ExtensionContext;
@@ -83,14 +84,16 @@ export class CommandManager {
 		}, (progress, token) => {
 			token.onCancellationRequested(() => cancelled = true);
 
-			const generator = new Generator(root.uri, lang, true);
+			const config = vscode.workspace.getConfiguration('crabviz');
+			const filter = config.get<SymbolFilter>('symbolFilter', {});
+			const generator = new Generator(root.uri, lang, filter, config.get<boolean>('keepExternalCalls', false));
 			return generator.generateCallGraph(files.get(lang)!, progress, token);
 		})
 		.then(graph => {
 			if (cancelled) { return; }
 
 			const panel = new CallGraphPanel(this.context.extensionUri);
-			panel.showCallGraph(graph, root.uri.path);
+			panel.showCallGraph(graph);
 		});
 	}
 
@@ -109,8 +112,10 @@ export class CommandManager {
 			location: vscode.ProgressLocation.Window,
 			title: "Crabviz: Generating call graph",
 		}, _ => {
-			const generator = new Generator(root.uri, lang, false);
-			return generator.generateFuncCallGraph(uri, anchor, ig);
+			const config = vscode.workspace.getConfiguration('crabviz');
+			const depth = config.get<number>('funcCallGraphDepth', 0);
+			const generator = new Generator(root.uri, lang, { languageDefaults: false }, config.get<boolean>('keepExternalCalls', false));
+			return generator.generateFuncCallGraph(uri, anchor, ig, depth > 0 ? depth : undefined);
 		})
 		.then((res) => {
 			if (!res) {
@@ -121,7 +126,7 @@ export class CommandManager {
 			const [graph, funcPos] = res;
 
 			const panel = new CallGraphPanel(this.context.extensionUri);
-			panel.showCallGraph(graph, root.uri.path, funcPos);
+			panel.showCallGraph(graph, { ...funcPos, path: toWorkspacePath(funcPos.path) });
 		});
 	}
 
@@ -6,9 +6,20 @@ import { Ignore } from 'ignore';
 import * as path from "path";
 
 const FUNC_KINDS: readonly vscode.SymbolKind[] = [vscode.SymbolKind.Function, vscode.SymbolKind.Method, vscode.SymbolKind.Constructor];
+const TYPE_KINDS: readonly vscode.SymbolKind[] = [vscode.SymbolKind.Class, vscode.SymbolKind.Struct, vscode.SymbolKind.Interface];
 
 const isWindows = process.platform === 'win32';
 
+export type SymbolFilter = {
+  languageDefaults?: boolean;
+  includeKinds?: string[];
+  excludeKinds?: string[];
+  includeNames?: string[];
+  excludeNames?: string[];
+  includePaths?: string[];
+  excludePaths?: string[];
+};
+
 export type GlobalPosition = {
   path: string;
   line: number;
@@ -18,10 +29,17 @@ export type GlobalPosition = {
 export class Generator {
   private root: string;
   private inner: GraphGenerator;
+  private keepExternalCalls: boolean;
 
-  public constructor(root: vscode.Uri, lang: string, filter: boolean) {
+  public constructor(root: vscode.Uri, lang: string, filter: SymbolFilter, keepExternalCalls = false) {
     this.root = normalizedPath(root.path);
     this.inner = new GraphGenerator(lang, filter);
+    this.keepExternalCalls = keepExternalCalls;
+    this.inner.set_keep_external_calls(keepExternalCalls);
+
+    for (const folder of vscode.workspace.workspaceFolders ?? []) {
+      this.inner.add_root(folder.name, folder.uri.path);
+    }
   }
 
   public async generateCallGraph(
@@ -50,7 +68,15 @@ export class Generator {
 
       const filePath = normalizedPath(file.path);
 
-      if (!this.inner.add_file(filePath, symbols)) {
+      let added: boolean;
+      try {
+        added = this.inner.add_file(filePath, symbols);
+      } catch (e) {
+        vscode.window.showWarningMessage(`Skipped '${file.fsPath}': ${e}`);
+        added = false;
+      }
+
+      if (!added) {
         finishedCount += 1;
         progress.report({ message: `${finishedCount} / ${files.length}`, increment: 100 / files.length });
         continue;
@@ -76,7 +102,13 @@ export class Generator {
             for await (const item of items) {
               await this.resolveCallsInFiles(item, funcMap);
             }
-          } else if (symbol.kind === vscode.SymbolKind.Interface) {
+          }
+
+          if (TYPE_KINDS.includes(symbol.kind)) {
+            await this.resolveSupertypes(file, filePath, symbol.selectionRange.start);
+          }
+
+          if (symbol.kind === vscode.SymbolKind.Interface) {
             await vscode.commands.executeCommand<vscode.Location[] | vscode.LocationLink[]>('vscode.executeImplementationProvider', file, symbol.selectionRange.start)
               .then(result => {
                 if (result.length <= 0) {
@@ -115,7 +147,7 @@ export class Generator {
     return this.inner.gen_graph();
   }
 
-  async generateFuncCallGraph(uri: vscode.Uri, anchor: vscode.Position, ig: Ignore): Promise<any | null> {
+  async generateFuncCallGraph(uri: vscode.Uri, anchor: vscode.Position, ig: Ignore, maxDepth?: number): Promise<any | null> {
     const files = new Map<string, VisitedFile>();
 
     let items: vscode.CallHierarchyItem[];
@@ -142,8 +174,8 @@ export class Generator {
         character: itemStart.character,
       };
 
-      await this.resolveIncomingCalls(item, files, ig);
-      await this.resolveOutgoingCalls(item, files, ig);
+      await this.resolveIncomingCalls(item, files, ig, 0, maxDepth);
+      await this.resolveOutgoingCalls(item, files, ig, 0, maxDepth);
     }
 
     for await (const file of files.values()) {
@@ -158,10 +190,21 @@ export class Generator {
       const funcs = file.sortedFuncs().filter(rng => !rng.isEmpty);
       symbols = this.filterSymbols(symbols, funcs);
 
-      this.inner.add_file(normalizedPath(file.uri.path), symbols);
+      try {
+        this.inner.add_file(normalizedPath(file.uri.path), symbols);
+      } catch (e) {
+        console.log(`Skipped '${file.uri.fsPath}': ${e}`);
+      }
     }
 
-    return [this.inner.gen_graph(), funcPos!];
+    const graph = this.inner.gen_func_graph(
+      funcPos!.path,
+      { line: funcPos!.line, character: funcPos!.character },
+      maxDepth,
+      FuncCallDirection.BIDIRECTION,
+    );
+
+    return [graph, funcPos!];
   }
 
   filterSymbols(symbols: vscode.DocumentSymbol[], funcs: vscode.Range[], ctx = { i: 0 }): vscode.DocumentSymbol[] {
@@ -189,6 +232,30 @@ export class Generator {
       });
   }
 
+  async resolveSupertypes(file: vscode.Uri, filePath: string, position: vscode.Position) {
+    let items: vscode.TypeHierarchyItem[];
+    try {
+      items = await vscode.commands.executeCommand<vscode.TypeHierarchyItem[]>('vscode.prepareTypeHierarchy', file, position);
+    } catch (e) {
+      console.log(e);
+      return;
+    }
+
+    for await (const item of items ?? []) {
+      await vscode.commands.executeCommand<vscode.TypeHierarchyItem[]>('vscode.provideSupertypes', item)
+        .then(supertypes => {
+          if (isWindows) {
+            supertypes.forEach(t => t.uri = t.uri.with({ path: normalizedPath(t.uri.path) }));
+          }
+
+          this.inner.add_type_hierarchy_supertypes(filePath, position, supertypes);
+        })
+        .then(undefined, err => {
+          console.log(err);
+        });
+    }
+  }
+
   async resolveCallsInFiles(item: vscode.CallHierarchyItem, funcMap: Map<string, Set<string>>) {
     await vscode.commands.executeCommand<vscode.CallHierarchyIncomingCall[]>('vscode.provideIncomingCalls', item)
       .then(async calls => {
@@ -214,9 +281,31 @@ export class Generator {
       .then(undefined, err => {
         console.error(err);
       });
+
+    // calls into the analyzed files are all found as incoming calls, but those leaving them only as outgoing calls
+    if (this.keepExternalCalls) {
+      await this.resolveExternalCalls(item, funcMap);
+    }
   }
 
-  async resolveIncomingCalls(item: vscode.CallHierarchyItem, funcMap: Map<string, VisitedFile>, ig: Ignore) {
+  async resolveExternalCalls(item: vscode.CallHierarchyItem, funcMap: Map<string, Set<string>>) {
+    await vscode.commands.executeCommand<vscode.CallHierarchyOutgoingCall[]>('vscode.provideOutgoingCalls', item)
+      .then(calls => {
+        if (isWindows) {
+          calls.forEach(call => call.to.uri = call.to.uri.with({ path: normalizedPath(call.to.uri.path) }));
+        }
+
+        calls = calls.filter(call => !funcMap.has(call.to.uri.path));
+        if (calls.length > 0) {
+          this.inner.add_outgoing_calls(normalizedPath(item.uri.path), item.selectionRange.start, calls);
+        }
+      })
+      .then(undefined, err => {
+        console.error(err);
+      });
+  }
+
+  async resolveIncomingCalls(item: vscode.CallHierarchyItem, funcMap: Map<string, VisitedFile>, ig: Ignore, depth: number, maxDepth?: number) {
     await vscode.commands.executeCommand<vscode.CallHierarchyIncomingCall[]>('vscode.provideIncomingCalls', item)
       .then(async calls => {
         if (isWindows) {
@@ -225,7 +314,7 @@ export class Generator {
 
         const itemNormalizedPath = normalizedPath(item.uri.path);
         this.inner.add_incoming_calls(itemNormalizedPath, item.selectionRange.start, calls);
-        funcMap.get(itemNormalizedPath)!.visitFunc(item.selectionRange, FuncCallDirection.INCOMING);
+        funcMap.get(itemNormalizedPath)!.visitFunc(item.selectionRange, FuncCallDirection.INCOMING, depth);
 
         calls = calls
           .filter(call => {
@@ -238,11 +327,17 @@ export class Generator {
               funcMap.set(uri.path, file);
             }
 
-            return !file.skip && !file.hasVisitedFunc(call.from.selectionRange.start, FuncCallDirection.INCOMING);
+            return !file.skip && !file.hasVisitedFunc(call.from.selectionRange.start, FuncCallDirection.INCOMING, depth + 1);
           });
 
+        // callers at the depth limit are shown, but not followed
+        if (maxDepth !== undefined && depth + 1 >= maxDepth) {
+          calls.forEach(call => funcMap.get(call.from.uri.path)!.addFunc(call.from.selectionRange));
+          return;
+        }
+
         for await (const call of calls) {
-          await this.resolveIncomingCalls(call.from, funcMap, ig);
+          await this.resolveIncomingCalls(call.from, funcMap, ig, depth + 1, maxDepth);
         }
       })
       .then(undefined, err => {
@@ -250,7 +345,7 @@ export class Generator {
       });
   }
 
-  async resolveOutgoingCalls(item: vscode.CallHierarchyItem, funcMap: Map<string, VisitedFile>, ig: Ignore) {
+  async resolveOutgoingCalls(item: vscode.CallHierarchyItem, funcMap: Map<string, VisitedFile>, ig: Ignore, depth: number, maxDepth?: number) {
     await vscode.commands.executeCommand<vscode.CallHierarchyOutgoingCall[]>('vscode.provideOutgoingCalls', item)
       .then(async calls => {
         if (isWindows) {
@@ -259,7 +354,7 @@ export class Generator {
 
         const itemNormalizedPath = normalizedPath(item.uri.path);
         this.inner.add_outgoing_calls(itemNormalizedPath, item.selectionRange.start, calls);
-        funcMap.get(itemNormalizedPath)!.visitFunc(item.selectionRange, FuncCallDirection.OUTGOING);
+        funcMap.get(itemNormalizedPath)!.visitFunc(item.selectionRange, FuncCallDirection.OUTGOING, depth);
 
         calls = calls
           .filter(call => {
@@ -276,11 +371,17 @@ export class Generator {
               funcMap.set(uri.path, file);
             }
 
-            return !file.skip && !file.hasVisitedFunc(call.to.selectionRange.start, FuncCallDirection.OUTGOING);
+            return !file.skip && !file.hasVisitedFunc(call.to.selectionRange.start, FuncCallDirection.OUTGOING, depth + 1);
           });
 
+        // callees at the depth limit are shown, but not followed
+        if (maxDepth !== undefined && depth + 1 >= maxDepth) {
+          calls.forEach(call => funcMap.get(call.to.uri.path)!.addFunc(call.to.selectionRange));
+          return;
+        }
+
         for await (const call of calls) {
-          await this.resolveOutgoingCalls(call.to, funcMap, ig);
+          await this.resolveOutgoingCalls(call.to, funcMap, ig, depth + 1, maxDepth);
         }
       })
       .then(undefined, err => {
@@ -299,14 +400,24 @@ class VisitedFile {
   uri: vscode.Uri;
   skip: boolean;
   private funcs: Map<string, [vscode.Range, FuncCallDirection]>;
+  // the smallest depth each function was visited at, per direction
+  private depths: Map<string, number>;
 
   constructor(uri: vscode.Uri) {
     this.uri = uri;
     this.skip = false;
     this.funcs = new Map();
+    this.depths = new Map();
   }
 
-  visitFunc(rng: vscode.Range, direction: FuncCallDirection) {
+  addFunc(rng: vscode.Range) {
+    let key = keyFromPosition(rng.start);
+    if (!this.funcs.has(key)) {
+      this.funcs.set(key, [rng, 0]);
+    }
+  }
+
+  visitFunc(rng: vscode.Range, direction: FuncCallDirection, depth: number) {
     let key = keyFromPosition(rng.start);
     let val = this.funcs.get(key);
 
@@ -315,10 +426,16 @@ class VisitedFile {
     } else {
       val[1] |= direction;
     }
+
+    const depthKey = `${key} ${direction}`;
+    this.depths.set(depthKey, Math.min(depth, this.depths.get(depthKey) ?? depth));
   }
 
-  hasVisitedFunc(pos: vscode.Position, direction: FuncCallDirection): boolean {
-    return ((this.funcs.get(keyFromPosition(pos))?.[1] ?? 0) & direction) === direction;
+  // a function visited deeper than `depth` is visited again, as more of its calls are within the limit
+  hasVisitedFunc(pos: vscode.Position, direction: FuncCallDirection, depth: number): boolean {
+    const key = keyFromPosition(pos);
+    return ((this.funcs.get(key)?.[1] ?? 0) & direction) === direction
+      && this.depths.get(`${key} ${direction}`)! <= depth;
   }
 
   sortedFuncs(): vscode.Range[] {
@@ -0,0 +1,19 @@
+import * as vscode from 'vscode';
+
+// Graphs name files by the workspace folder containing them rather than by its location,
+// as `<folder name>/<relative path>`, and by the full path for files outside of every folder.
+
+export function toWorkspacePath(path: string): string {
+  const folder = (vscode.workspace.workspaceFolders ?? [])
+    .filter(folder => path.startsWith(folder.uri.path + '/'))
+    .reduce<vscode.WorkspaceFolder | undefined>((inner, folder) => !inner || folder.uri.path.length > inner.uri.path.length ? folder : inner, undefined);
+
+  return folder ? `${folder.name}/${path.substring(folder.uri.path.length + 1)}` : path;
+}
+
+export function fromWorkspacePath(path: string): vscode.Uri {
+  const sep = path.indexOf('/');
+  const folder = sep > 0 ? vscode.workspace.workspaceFolders?.find(folder => folder.name === path.substring(0, sep)) : undefined;
+
+  return folder ? vscode.Uri.joinPath(folder.uri, path.substring(sep + 1)) : vscode.Uri.file(path);
+}
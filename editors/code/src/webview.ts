@@ -1,6 +1,7 @@
 import * as vscode from 'vscode';
 
 import { GlobalPosition } from './generator';
+import { fromWorkspacePath } from './utils/workspace-path';
 
 export class CallGraphPanel {
 	public static readonly viewType = 'crabviz.callgraph';
@@ -36,7 +37,7 @@ export class CallGraphPanel {
 						this.save(msg.html, "html");
 						break;
 					case 'go to definition':
-						vscode.workspace.openTextDocument(vscode.Uri.file(msg.path))
+						vscode.workspace.openTextDocument(fromWorkspacePath(msg.path))
 							.then(doc => vscode.window.showTextDocument(doc))
 							.then(editor => {
 								let position = new vscode.Position(msg.ln, msg.col);
@@ -83,7 +84,7 @@ export class CallGraphPanel {
 		}
 	}
 
-	public showCallGraph(graph: any, root: string, focus: GlobalPosition | null = null) {
+	public showCallGraph(graph: any, focus: GlobalPosition | null = null) {
 		CallGraphPanel.currentPanel = this;
 
 		const nonce = getNonce();
@@ -103,7 +104,6 @@ export class CallGraphPanel {
 
 					document.crabvizProps = {
 						graph: ${JSON.stringify(graph)},
-						root: ${JSON.stringify(root)},
 						focus: ${JSON.stringify(focus)},
 					};
 
@@ -1,6 +1,7 @@
 import { Component } from "solid-js";
 
 import { Graph } from "./graph/types";
+import { workspacePath } from "./graph/utils";
 
 import "./styles/main.css";
 import "./App.css";
@@ -10,7 +11,6 @@ import GraphViewport from "./components/GraphViewport";
 
 const App: Component<{
   graph: Graph;
-  root: string;
   focus: {
     path: string;
     line: number;
@@ -19,7 +19,7 @@ const App: Component<{
 }> = (props) => {
   let focus: string | null = null;
   if (props.focus) {
-    const fileId = props.focus && props.graph.files.find((f) => f.path == props.focus!.path)
+    const fileId = props.focus && props.graph.files.find((f) => workspacePath(f) == props.focus!.path)
       ?.id!;
 
     focus = `${fileId}:${props.focus!.line}_${props.focus!.character}`;
@@ -31,7 +31,7 @@ const App: Component<{
         <Topbar focus={props.focus != null} />
       </div>
       <div id="container">
-        <GraphViewport graph={props.graph} root={props.root} focus={focus} />
+        <GraphViewport graph={props.graph} focus={focus} />
       </div>
     </>
   );
@@ -20,7 +20,6 @@ import "./GraphViewport.css";
 
 const GraphViewport: Component<{
   graph: Graph;
-  root: string;
   focus: string | null;
 }> = (props) => {
   const [
@@ -38,7 +37,7 @@ const GraphViewport: Component<{
 
       if (!cache.has(isCollapsed)) {
         const svg = await renderSVG(
-          convert(props.graph, props.root, isCollapsed),
+          convert(props.graph, isCollapsed),
           props.focus
         );
 
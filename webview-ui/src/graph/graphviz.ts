@@ -1,8 +1,8 @@
 import { Graph as VizGraph } from "@viz-js/viz";
 
-import { Graph, File, Symbol, Relation, RelationKind } from "./types";
+import { Graph, File, Symbol, Relation, RelationKind, ExternalPackage } from "./types";
 import { SymbolKind } from "../lsp";
-import { escapeHtml, splitDirectory, commonAncestorPath } from "./utils";
+import { escapeHtml, splitDirectory, commonAncestorPath, workspacePath } from "./utils";
 
 type Node = {
   name: string;
@@ -28,10 +28,11 @@ interface Attributes {
   [name: string]: string | number | boolean | { html: string };
 }
 
-export const convert = (graph: Graph, root: string, collapse: boolean): VizGraph => {
+export const convert = (graph: Graph, collapse: boolean): VizGraph => {
   const nodes = graph.files
-    .sort((a, b) => a.path.localeCompare(b.path))
-    .map((f) => file2node(f, collapse));
+    .map((f): [File, string] => [f, workspacePath(f)])
+    .sort(([, a], [, b]) => a.localeCompare(b))
+    .map(([f, path]) => file2node(f, path, collapse));
 
   const subgraph = nodes.reduce<Subgraph | undefined>((subgraph, node) => {
     if (!subgraph) {
@@ -71,14 +72,6 @@ export const convert = (graph: Graph, root: string, collapse: boolean): VizGraph
     return subgraph;
   }, undefined);
 
-  if (subgraph) {
-    const [rootParent, _] = splitDirectory(root);
-    subgraph.dir = subgraph.dir.substring(rootParent.length + 1);
-    subgraph.graphAttributes.label = {
-      html: subgraphTitle(subgraph.dir),
-    };
-  }
-
   return {
     graphAttributes: {
       rankdir: "LR",
@@ -97,7 +90,10 @@ export const convert = (graph: Graph, root: string, collapse: boolean): VizGraph
       arrowsize: "1.5",
       label: " ",
     },
-    subgraphs: subgraph ? [subgraph] : [],
+    subgraphs: [
+      ...(subgraph ? [subgraph] : []),
+      ...graph.externals.map((pkg) => package2subgraph(pkg, collapse)),
+    ],
     edges: collectEdges(graph.relations, collapse),
   };
 };
@@ -131,12 +127,21 @@ const createSubgraph = (function () {
   };
 })();
 
+// the files of an external package are shown by their paths relative to the package
+const package2subgraph = (pkg: ExternalPackage, collapse: boolean): Subgraph => {
+  const subgraph = createSubgraph(pkg.name);
+  subgraph.nodes = pkg.files.map((f) =>
+    file2node(f, `${pkg.name}/${f.relativePath}`, collapse)
+  );
+  return subgraph;
+};
+
 const subgraphTitle = (title: string): string => {
   return `<TABLE BORDER="0" BGCOLOR="lightgray" CELLPADDING="6" CELLBORDER="0"><TR><TD>${title}</TD></TR></TABLE>`;
 };
 
-const file2node = (file: File, collapsed: boolean = false): Node => {
-  const [dir, name] = splitDirectory(file.path);
+const file2node = (file: File, path: string, collapsed: boolean = false): Node => {
+  const [dir, name] = splitDirectory(path);
   const id = file.id.toString();
 
   return {
@@ -147,13 +152,13 @@ const file2node = (file: File, collapsed: boolean = false): Node => {
         html:
           collapsed || file.symbols.length <= 0
             ? `<TABLE BORDER="0" CELLBORDER="0" CELLSPACING="8" CELLPADDING="4">
-            <TR><TD HREF="${file.path}" WIDTH="200" BORDER="0" CELLPADDING="6">
+            <TR><TD HREF="${path}" WIDTH="200" BORDER="0" CELLPADDING="6">
             ${name}
             </TD></TR>
           </TABLE>`
             : `
           <TABLE BORDER="0" CELLBORDER="0" CELLSPACING="8" CELLPADDING="4">
-            <TR><TD HREF="${file.path}" WIDTH="230" BORDER="0" CELLPADDING="6">
+            <TR><TD HREF="${path}" WIDTH="230" BORDER="0" CELLPADDING="6">
             ${name}
             </TD></TR>
             ${file.symbols.map((s) => symbol2cell(file.id, s)).join("\n")}
@@ -168,7 +173,7 @@ const file2node = (file: File, collapsed: boolean = false): Node => {
 
 const symbol2cell = (fileId: number, symbol: Symbol): string => {
   const text = escapeHtml(symbol.name);
-  const port = `${symbol.range.start.line}_${symbol.range.start.character}`;
+  const port = `${symbol.selectionRange.start.line}_${symbol.selectionRange.start.character}`;
   const href = `HREF="${symbol.kind}"`;
 
   let icon = "";
@@ -1,26 +1,102 @@
-import { SymbolKind } from "../lsp";
+import { SymbolKind, SymbolTag } from "../lsp";
 
 export interface Graph {
+  roots: WorkspaceRoot[];
   files: File[];
+  externals: ExternalPackage[];
   relations: Relation[];
 }
 
+export interface ExternalPackage {
+  name: string;
+  files: File[];
+}
+
+export interface WorkspaceRoot {
+  name: string;
+}
+
 export interface File {
   id: number;
-  path: string;
+  root: string | null;
+  relativePath: string;
   symbols: Symbol[];
 }
 
 export interface Relation {
   from: GlobalPosition;
   to: GlobalPosition;
+  fromId: string;
+  toId: string;
   kind: RelationKind;
+  ranges: Range[];
+  count: number;
+  inCallCycle: boolean;
+  inFileCycle: boolean;
+}
+
+export interface Metrics {
+  fanIn: number;
+  fanOut: number;
+  transitiveCallers?: number;
+  pageRank: number;
+}
+
+export interface GraphMetrics {
+  symbols: ({ position: GlobalPosition } & Metrics)[];
+  files: ({ fileId: number } & Metrics)[];
+}
+
+export interface RelationIdentity {
+  fromId: string;
+  toId: string;
+  kind: RelationKind;
+}
+
+export interface GraphDiff {
+  addedFiles: string[];
+  removedFiles: string[];
+  addedSymbols: string[];
+  removedSymbols: string[];
+  addedRelations: RelationIdentity[];
+  removedRelations: RelationIdentity[];
+}
+
+export interface Cycles {
+  symbols: GlobalPosition[][];
+  files: number[][];
+}
+
+export interface DependencyGraph {
+  nodes: DependencyNode[];
+  edges: Dependency[];
+}
+
+export interface DependencyNode {
+  id: number;
+  path: string;
+  symbolCount: number;
+  fileCount: number;
+}
+
+export interface Dependency {
+  from: number;
+  to: number;
+  weight: number;
+  calls: number;
+  impls: number;
+  inherits: number;
 }
 
 export interface Symbol {
+  id: string;
   name: string;
+  qualifiedName: string;
+  detail: string | null;
   kind: SymbolKind;
+  tags: SymbolTag[];
   range: Range;
+  selectionRange: Range;
   children: Symbol[];
 }
 
@@ -45,3 +121,19 @@ export interface Position {
   line: number;
   character: number;
 }
+
+export interface PathPosition {
+  path: string;
+  position: Position;
+}
+
+export interface Diagnostics {
+  rejectedFiles: { path: string; reason: "filteredOut" | "duplicate" }[];
+  unresolvedCalls: { from: PathPosition; to: PathPosition; name: string }[];
+  droppedRelations: {
+    from: PathPosition;
+    to: PathPosition;
+    kind: RelationKind;
+    reason: "unknownSource" | "unknownTarget";
+  }[];
+}
@@ -1,3 +1,10 @@
+import { File } from "./types";
+
+// the path prefixed by the name of the workspace folder, or the full path for files outside of every folder
+export const workspacePath = (file: File): string => {
+  return file.root ? `${file.root}/${file.relativePath}` : file.relativePath;
+};
+
 export const splitDirectory = (path: string): [string, string] => {
   const lastSep = Math.max(path.lastIndexOf("/"), path.lastIndexOf("\\"));
   return [path.substring(0, lastSep), path.substring(lastSep + 1, path.length)];
@@ -27,6 +27,10 @@ export enum SymbolKind {
   TYPE_PARAMETER,
 }
 
+export enum SymbolTag {
+  DEPRECATED = 1,
+}
+
 export function kindNum2Name(kind: string): string {
   switch (kind) {
     case "1": return "file";